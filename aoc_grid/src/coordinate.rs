@@ -3,65 +3,159 @@
 //! This module provides a simple 2D coordinate type that can be used to represent points on a
 //! grid. It is intended to be used in conjunction with the `Grid` type, which provides a 2D grid
 //! data structure.
+//!
+//! The component type defaults to `isize`, which is what `Grid` uses, but any primitive integer
+//! can be used (e.g. `Coordinate<i64>` for puzzle math that doesn't fit a grid). The regular
+//! operators behave like the primitive operators (panicking on overflow in debug builds); use the
+//! `checked_*` and `wrapping_*` methods when overflow is expected.
 
 use std::fmt::Debug;
 use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use crate::grid_index::GridIndex;
+use crate::integer::Integer;
 
-#[derive(Clone, Copy, Eq, Hash)]
-pub struct Coordinate(pub isize, pub isize);
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Coordinate<N = isize>(pub N, pub N);
 
-impl Coordinate {
+impl<N: Integer> Coordinate<N> {
     #[must_use]
-    pub const fn new(x: isize, y: isize) -> Self {
+    pub const fn new(x: N, y: N) -> Self {
         Self(x, y)
     }
 
-    pub const fn manhattan_distance(self, other: Self) -> usize {
-        ((self.0 - other.0).abs() + (self.1 - other.1).abs()) as usize
+    pub fn manhattan_distance(self, other: Self) -> N::Unsigned {
+        self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
+    }
+
+    // Distance when diagonal steps are allowed (the number of king moves on a chess board).
+    pub fn chebyshev_distance(self, other: Self) -> N::Unsigned {
+        self.0.abs_diff(other.0).max(self.1.abs_diff(other.1))
+    }
+
+    pub fn dot(self, other: Self) -> N {
+        self.0 * other.0 + self.1 * other.1
+    }
+
+    // The z-component of the 3D cross product. Zero when both vectors are collinear.
+    pub fn cross(self, other: Self) -> N {
+        self.0 * other.1 - self.1 * other.0
+    }
+
+    // Wraps both components into `0..modulus`, per axis. Useful for toroidal maps.
+    #[must_use]
+    pub fn rem_euclid(self, modulus: Self) -> Self {
+        Self(self.0.rem_euclid(modulus.0), self.1.rem_euclid(modulus.1))
+    }
+
+    #[must_use]
+    pub fn component_min(self, other: Self) -> Self {
+        Self(self.0.min(other.0), self.1.min(other.1))
+    }
+
+    #[must_use]
+    pub fn component_max(self, other: Self) -> Self {
+        Self(self.0.max(other.0), self.1.max(other.1))
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        Some(Self(
+            self.0.checked_add(other.0)?,
+            self.1.checked_add(other.1)?,
+        ))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        Some(Self(
+            self.0.checked_sub(other.0)?,
+            self.1.checked_sub(other.1)?,
+        ))
+    }
+
+    pub fn checked_mul(self, factor: N) -> Option<Self> {
+        Some(Self(
+            self.0.checked_mul(factor)?,
+            self.1.checked_mul(factor)?,
+        ))
+    }
+
+    #[must_use]
+    pub fn wrapping_add(self, other: Self) -> Self {
+        Self(self.0.wrapping_add(other.0), self.1.wrapping_add(other.1))
+    }
+
+    #[must_use]
+    pub fn wrapping_sub(self, other: Self) -> Self {
+        Self(self.0.wrapping_sub(other.0), self.1.wrapping_sub(other.1))
+    }
+
+    #[must_use]
+    pub fn wrapping_mul(self, factor: N) -> Self {
+        Self(self.0.wrapping_mul(factor), self.1.wrapping_mul(factor))
+    }
+
+    // Converts to a coordinate with a wider component type. This can't lose information.
+    pub fn convert<M: From<N>>(self) -> Coordinate<M> {
+        Coordinate(M::from(self.0), M::from(self.1))
+    }
+
+    // Converts to a coordinate with another component type, if both components fit.
+    pub fn try_convert<M: TryFrom<N>>(self) -> Option<Coordinate<M>> {
+        Some(Coordinate(
+            M::try_from(self.0).ok()?,
+            M::try_from(self.1).ok()?,
+        ))
     }
 }
 
-impl Debug for Coordinate {
+impl<N: Debug> Debug for Coordinate<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
+        write!(f, "({:?}, {:?})", self.0, self.1)
+    }
+}
+
+impl<N: Copy> From<&(N, N)> for Coordinate<N> {
+    fn from(&(x, y): &(N, N)) -> Self {
+        Self(x, y)
     }
 }
 
-impl From<&(isize, isize)> for Coordinate {
-    fn from(&(x, y): &(isize, isize)) -> Self {
+impl<N> From<(N, N)> for Coordinate<N> {
+    fn from((x, y): (N, N)) -> Self {
         Self(x, y)
     }
 }
 
-impl From<Coordinate> for (isize, isize) {
-    fn from(coord: Coordinate) -> Self {
+impl<N> From<Coordinate<N>> for (N, N) {
+    fn from(coord: Coordinate<N>) -> Self {
         (coord.0, coord.1)
     }
 }
 
-impl From<&Coordinate> for (isize, isize) {
-    fn from(coord: &Coordinate) -> Self {
+impl<N: Copy> From<&Coordinate<N>> for (N, N) {
+    fn from(coord: &Coordinate<N>) -> Self {
         (coord.0, coord.1)
     }
 }
 
-impl<T> PartialEq<T> for Coordinate
-where
-    T: GridIndex,
-{
-    fn eq(&self, other: &T) -> bool {
-        self.0 == other.x() && self.1 == other.y()
+impl<N: PartialEq> PartialEq<&Coordinate<N>> for Coordinate<N> {
+    fn eq(&self, other: &&Coordinate<N>) -> bool {
+        self == *other
+    }
+}
+
+impl<N: PartialEq> PartialEq<(N, N)> for Coordinate<N> {
+    fn eq(&self, other: &(N, N)) -> bool {
+        self.0 == other.0 && self.1 == other.1
     }
 }
 
 // =================================================================================================
 // Adding
 
-impl<T> Add<T> for Coordinate
+impl<N, T> Add<T> for Coordinate<N>
 where
-    T: Into<(isize, isize)>,
+    N: Integer,
+    T: Into<(N, N)>,
 {
     type Output = Self;
 
@@ -71,9 +165,10 @@ where
     }
 }
 
-impl<T> AddAssign<T> for Coordinate
+impl<N, T> AddAssign<T> for Coordinate<N>
 where
-    T: Into<(isize, isize)>,
+    N: Integer,
+    T: Into<(N, N)>,
 {
     fn add_assign(&mut self, other: T) {
         let other = other.into();
@@ -85,9 +180,10 @@ where
 // =================================================================================================
 // Subtraction
 
-impl<T> Sub<T> for Coordinate
+impl<N, T> Sub<T> for Coordinate<N>
 where
-    T: Into<(isize, isize)>,
+    N: Integer,
+    T: Into<(N, N)>,
 {
     type Output = Self;
 
@@ -97,9 +193,10 @@ where
     }
 }
 
-impl<T> SubAssign<T> for Coordinate
+impl<N, T> SubAssign<T> for Coordinate<N>
 where
-    T: Into<(isize, isize)>,
+    N: Integer,
+    T: Into<(N, N)>,
 {
     fn sub_assign(&mut self, other: T) {
         let other = other.into();
@@ -111,16 +208,16 @@ where
 // =================================================================================================
 // Multiplication
 
-impl Mul<isize> for Coordinate {
+impl<N: Integer> Mul<N> for Coordinate<N> {
     type Output = Self;
 
-    fn mul(self, other: isize) -> Self::Output {
+    fn mul(self, other: N) -> Self::Output {
         Self(self.0 * other, self.1 * other)
     }
 }
 
-impl MulAssign<isize> for Coordinate {
-    fn mul_assign(&mut self, other: isize) {
+impl<N: Integer> MulAssign<N> for Coordinate<N> {
+    fn mul_assign(&mut self, other: N) {
         self.0 *= other;
         self.1 *= other;
     }
@@ -147,10 +244,65 @@ impl MulAssign<usize> for Coordinate {
 // =================================================================================================
 // Negation
 
-impl Neg for Coordinate {
+impl<N: Neg<Output = N>> Neg for Coordinate<N> {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
         Self(-self.0, -self.1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let a = Coordinate(1isize, -2);
+        let b = Coordinate(-3isize, 4);
+        assert_eq!(a.manhattan_distance(b), 10usize);
+        assert_eq!(a.chebyshev_distance(b), 6usize);
+
+        let a = Coordinate(i8::MIN, 0);
+        let b = Coordinate(i8::MAX, 0);
+        assert_eq!(a.manhattan_distance(b), 255u8);
+    }
+
+    #[test]
+    fn test_products() {
+        let a = Coordinate(2i64, 3);
+        let b = Coordinate(5i64, 7);
+        assert_eq!(a.dot(b), 31);
+        assert_eq!(a.cross(b), -1);
+        assert_eq!(a.cross(a * 3), 0);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let big = Coordinate(i64::MAX, 0);
+        assert_eq!(big.checked_add(Coordinate(1, 0)), None);
+        assert_eq!(big.checked_mul(2), None);
+        assert_eq!(big.wrapping_add(Coordinate(1, 1)), Coordinate(i64::MIN, 1));
+        assert_eq!(Coordinate(0u32, 1).checked_sub(Coordinate(1, 0)), None);
+    }
+
+    #[test]
+    fn test_rem_euclid_and_bounds() {
+        let c = Coordinate(-1i64, 12);
+        assert_eq!(c.rem_euclid(Coordinate(11, 7)), Coordinate(10, 5));
+        assert_eq!(c.component_min(Coordinate(0, 0)), Coordinate(-1, 0));
+        assert_eq!(c.component_max(Coordinate(0, 0)), Coordinate(0, 12));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let c = Coordinate(-1i32, 2);
+        assert_eq!(c.convert::<i64>(), Coordinate(-1i64, 2));
+        assert_eq!(c.try_convert::<u8>(), None);
+        assert_eq!(
+            Coordinate(300i64, 2).try_convert::<isize>(),
+            Some(Coordinate(300, 2))
+        );
+        assert_eq!(Coordinate(300i64, 2).try_convert::<u8>(), None);
+    }
+}
@@ -299,6 +299,10 @@ mod tests {
         assert_eq!(grid.get((0, -1)), None);
         assert_eq!(grid.get((-1, 0)), None);
         assert_eq!(grid.get((-1, -1)), None);
+
+        // Out of range values must not wrap around into the grid
+        assert_eq!(grid.get(((1u128 << 64) + 1, 0u128)), None);
+        assert_eq!(grid.get((0i128, i128::MIN + 2)), None);
     }
 
     #[test]
@@ -1,6 +1,9 @@
+use crate::integer::Integer;
 use crate::Coordinate;
 
 // A trait for types that can be used as indices in a grid.
+//
+// Values that don't fit in an `isize` are clamped, which puts them outside of any grid.
 pub trait GridIndex {
     fn x(&self) -> isize;
     fn y(&self) -> isize;
@@ -11,20 +14,20 @@ macro_rules! implement_conversion {
     ( $t:ty ) => {
         impl GridIndex for ($t, $t) {
             fn x(&self) -> isize {
-                self.0 as isize
+                $crate::integer::Integer::to_isize_saturating(self.0)
             }
 
             fn y(&self) -> isize {
-                self.1 as isize
+                $crate::integer::Integer::to_isize_saturating(self.1)
             }
         }
         impl GridIndex for &($t, $t) {
             fn x(&self) -> isize {
-                self.0 as isize
+                $crate::integer::Integer::to_isize_saturating(self.0)
             }
 
             fn y(&self) -> isize {
-                self.1 as isize
+                $crate::integer::Integer::to_isize_saturating(self.1)
             }
         }
     };
@@ -43,21 +46,21 @@ implement_conversion!(u64);
 implement_conversion!(i128);
 implement_conversion!(u128);
 
-impl GridIndex for Coordinate {
+impl<N: Integer> GridIndex for Coordinate<N> {
     fn x(&self) -> isize {
-        self.0
+        self.0.to_isize_saturating()
     }
 
     fn y(&self) -> isize {
-        self.1
+        self.1.to_isize_saturating()
     }
 }
-impl GridIndex for &Coordinate {
+impl<N: Integer> GridIndex for &Coordinate<N> {
     fn x(&self) -> isize {
-        self.0
+        self.0.to_isize_saturating()
     }
 
     fn y(&self) -> isize {
-        self.1
+        self.1.to_isize_saturating()
     }
 }
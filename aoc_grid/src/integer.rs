@@ -0,0 +1,137 @@
+//! Integer abstraction for coordinates.
+//!
+//! The `Integer` trait captures the operations `Coordinate` needs from its component type, so a
+//! single coordinate type can be used with `isize` grids as well as with `i64`/`i128` puzzle math.
+
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Rem, Sub, SubAssign};
+
+/// A primitive integer type that can be used as a coordinate component.
+pub trait Integer:
+    Copy
+    + Debug
+    + Display
+    + Default
+    + Eq
+    + Ord
+    + Hash
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+{
+    /// The unsigned type of the same width, used for distances.
+    type Unsigned: Integer;
+
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+
+    /// Absolute difference between two values, which never overflows.
+    fn abs_diff(self, other: Self) -> Self::Unsigned;
+
+    /// Converts to `isize`, clamping values that don't fit to `isize::MIN` or `isize::MAX`.
+    ///
+    /// Clamped values are always outside of any grid, so they can't alias a valid cell.
+    fn to_isize_saturating(self) -> isize;
+}
+
+macro_rules! implement_integer {
+    ( $t:ty, $u:ty ) => {
+        impl Integer for $t {
+            type Unsigned = $u;
+
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn wrapping_add(self, rhs: Self) -> Self {
+                <$t>::wrapping_add(self, rhs)
+            }
+
+            fn wrapping_sub(self, rhs: Self) -> Self {
+                <$t>::wrapping_sub(self, rhs)
+            }
+
+            fn wrapping_mul(self, rhs: Self) -> Self {
+                <$t>::wrapping_mul(self, rhs)
+            }
+
+            fn rem_euclid(self, rhs: Self) -> Self {
+                <$t>::rem_euclid(self, rhs)
+            }
+
+            fn abs_diff(self, other: Self) -> Self::Unsigned {
+                <$t>::abs_diff(self, other)
+            }
+
+            fn to_isize_saturating(self) -> isize {
+                match isize::try_from(self) {
+                    Ok(value) => value,
+                    Err(_) if self < Self::ZERO => isize::MIN,
+                    Err(_) => isize::MAX,
+                }
+            }
+        }
+    };
+}
+
+implement_integer!(isize, usize);
+implement_integer!(usize, usize);
+implement_integer!(i8, u8);
+implement_integer!(u8, u8);
+implement_integer!(i16, u16);
+implement_integer!(u16, u16);
+implement_integer!(i32, u32);
+implement_integer!(u32, u32);
+implement_integer!(i64, u64);
+implement_integer!(u64, u64);
+implement_integer!(i128, u128);
+implement_integer!(u128, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_isize_saturating() {
+        assert_eq!(5u8.to_isize_saturating(), 5);
+        assert_eq!((-5i64).to_isize_saturating(), -5);
+        assert_eq!(u128::MAX.to_isize_saturating(), isize::MAX);
+        assert_eq!(i128::MIN.to_isize_saturating(), isize::MIN);
+        assert_eq!(((1u128 << 64) + 1).to_isize_saturating(), isize::MAX);
+    }
+
+    #[test]
+    fn test_abs_diff() {
+        assert_eq!(Integer::abs_diff(-3i8, 4i8), 7u8);
+        assert_eq!(Integer::abs_diff(i64::MIN, i64::MAX), u64::MAX);
+    }
+}
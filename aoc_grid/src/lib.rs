@@ -2,8 +2,10 @@ pub mod coordinate;
 pub mod direction;
 pub mod grid;
 pub mod grid_index;
+pub mod integer;
 
 // Main exports
 pub use coordinate::Coordinate;
 pub use direction::Direction;
 pub use grid::Grid;
+pub use integer::Integer;
@@ -0,0 +1,222 @@
+//! Cycle detection for iterated state machines.
+//!
+//! A deterministic simulation that revisits a state will loop forever. These functions find where
+//! that loop starts and how long it is, so a simulation can be skipped ahead to any step.
+//!
+//! - `floyd` and `brent` only need `PartialEq`, and use constant memory, but evaluate the step
+//!   function several times per state.
+//! - `detect_cycle` and `detect_cycle_by_key` remember every state (or a fingerprint of it), and
+//!   work on any iterator, including ones that end.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A detected cycle: states `start..start + length` repeat forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    /// The index of the first state that is part of the cycle.
+    pub start: usize,
+    /// The number of states in the cycle, at least 1.
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The earliest step that has the same state as step `n`.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Floyd's tortoise and hare algorithm. `step` must be deterministic, and the states must
+/// eventually repeat, otherwise this never returns.
+pub fn floyd<S: PartialEq + Clone>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // Find a meeting point inside the cycle
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        hare = step(&hare);
+    }
+
+    // The distance from the start to the cycle equals the distance from the meeting point to the
+    // cycle, so walking both at the same speed finds the start.
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Brent's algorithm. Same contract as `floyd`, but usually needs fewer steps.
+pub fn brent<S: PartialEq + Clone>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // Find the cycle length by searching in windows of increasing powers of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // Walk two states that are `length` apart until they meet at the start of the cycle
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Finds the first repeated state in a sequence, or `None` if the sequence ends first.
+pub fn detect_cycle<S: Hash + Eq + Clone>(states: impl IntoIterator<Item = S>) -> Option<Cycle> {
+    detect_cycle_by_key(states, S::clone).map(|(cycle, _)| cycle)
+}
+
+/// Like `detect_cycle`, but compares states by a fingerprint. The fingerprint must identify the
+/// state, otherwise two different states may be mistaken for a cycle.
+///
+/// The sequence is consumed up to (and including) the first repeated state, which is returned
+/// along with the cycle.
+pub fn detect_cycle_by_key<S, K: Hash + Eq>(
+    states: impl IntoIterator<Item = S>,
+    mut key: impl FnMut(&S) -> K,
+) -> Option<(Cycle, S)> {
+    let mut seen = HashMap::new();
+    for (index, state) in states.into_iter().enumerate() {
+        if let Some(start) = seen.insert(key(&state), index) {
+            return Some((
+                Cycle {
+                    start,
+                    length: index - start,
+                },
+                state,
+            ));
+        }
+    }
+    None
+}
+
+/// The state after `n` steps. Stops simulating as soon as a cycle is found, and looks up the
+/// state in the cycle instead, so `n` can be far larger than what could be simulated.
+pub fn fast_forward<S: Hash + Eq + Clone>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    n: usize,
+) -> S {
+    let mut history = vec![initial.clone()];
+    let mut seen = HashMap::from([(initial, 0)]);
+
+    while history.len() <= n {
+        let state = step(history.last().unwrap());
+        if let Some(&start) = seen.get(&state) {
+            let cycle = Cycle {
+                start,
+                length: history.len() - start,
+            };
+            return history.swap_remove(cycle.equivalent_step(n));
+        }
+        seen.insert(state.clone(), history.len());
+        history.push(state);
+    }
+
+    history.pop().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    // x -> x^2 + 1 mod 255, starting at 3: 3, 10, 101, 2, 5, 26, 167, 95, 101, ...
+    fn square_plus_one(x: &u64) -> u64 {
+        (x * x + 1) % 255
+    }
+
+    #[test]
+    fn test_equivalent_step() {
+        let cycle = Cycle {
+            start: 2,
+            length: 6,
+        };
+        assert_eq!(cycle.equivalent_step(1), 1);
+        assert_eq!(cycle.equivalent_step(2), 2);
+        assert_eq!(cycle.equivalent_step(8), 2);
+        assert_eq!(cycle.equivalent_step(1_000_000_000), 4);
+    }
+
+    #[rstest]
+    #[case::tail(3, Cycle { start: 2, length: 6 })]
+    #[case::no_tail(2, Cycle { start: 0, length: 6 })]
+    fn test_detectors_agree(#[case] initial: u64, #[case] expected: Cycle) {
+        let states = std::iter::successors(Some(initial), |x| Some(square_plus_one(x)));
+        assert_eq!(floyd(initial, square_plus_one), expected);
+        assert_eq!(brent(initial, square_plus_one), expected);
+        assert_eq!(detect_cycle(states), Some(expected));
+    }
+
+    #[test]
+    fn test_detect_cycle_by_key() {
+        // Only the position matters, the step counter is ignored
+        let states = (0..).map(|step| (step % 4, step));
+        let (cycle, state) = detect_cycle_by_key(states, |&(position, _)| position).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+        assert_eq!(state, (0, 4));
+    }
+
+    #[test]
+    fn test_detect_cycle_ends() {
+        assert_eq!(detect_cycle([1, 2, 3]), None);
+        assert_eq!(
+            detect_cycle([1, 2, 3, 2]),
+            Some(Cycle {
+                start: 1,
+                length: 2
+            })
+        );
+    }
+
+    #[rstest]
+    #[case(0, 3)]
+    #[case(5, 26)]
+    #[case(8, 101)]
+    #[case(1_000_000_000_000, 5)]
+    fn test_fast_forward(#[case] n: usize, #[case] expected: u64) {
+        assert_eq!(fast_forward(3, square_plus_one, n), expected);
+    }
+}
@@ -0,0 +1,118 @@
+//! Decimal digit manipulation, without converting numbers to strings.
+
+/// Digit operations on unsigned integers.
+pub trait Digits: Sized {
+    /// Number of decimal digits. Zero has one digit.
+    fn digit_count(self) -> u32;
+
+    /// Splits off the last `count` digits: `1234.split_digits(1) == (123, 4)`.
+    fn split_digits(self, count: u32) -> (Self, Self);
+
+    /// Splits a number with an even number of digits into its two halves:
+    /// `1234.split_in_half() == Some((12, 34))`. Returns `None` for an odd number of digits.
+    fn split_in_half(self) -> Option<(Self, Self)>;
+
+    /// Appends the digits of `other`: `12.concat_digits(345) == 12345`. Returns `None` on
+    /// overflow.
+    fn concat_digits(self, other: Self) -> Option<Self>;
+
+    /// The decimal digits, most significant first.
+    fn to_digits(self) -> Vec<u8>;
+}
+
+macro_rules! implement_digits {
+    ( $t:ty ) => {
+        impl Digits for $t {
+            fn digit_count(self) -> u32 {
+                self.checked_ilog10().unwrap_or(0) + 1
+            }
+
+            fn split_digits(self, count: u32) -> (Self, Self) {
+                match (10 as $t).checked_pow(count) {
+                    Some(power) => (self / power, self % power),
+                    // More digits requested than the type can hold
+                    None => (0, self),
+                }
+            }
+
+            fn split_in_half(self) -> Option<(Self, Self)> {
+                let digits = self.digit_count();
+                if digits.is_multiple_of(2) {
+                    Some(self.split_digits(digits / 2))
+                } else {
+                    None
+                }
+            }
+
+            fn concat_digits(self, other: Self) -> Option<Self> {
+                (10 as $t)
+                    .checked_pow(other.digit_count())?
+                    .checked_mul(self)?
+                    .checked_add(other)
+            }
+
+            fn to_digits(self) -> Vec<u8> {
+                let mut digits = Vec::with_capacity(self.digit_count() as usize);
+                let mut remaining = self;
+                loop {
+                    digits.push((remaining % 10) as u8);
+                    remaining /= 10;
+                    if remaining == 0 {
+                        break;
+                    }
+                }
+                digits.reverse();
+                digits
+            }
+        }
+    };
+}
+
+implement_digits!(u8);
+implement_digits!(u16);
+implement_digits!(u32);
+implement_digits!(u64);
+implement_digits!(u128);
+implement_digits!(usize);
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(9, 1)]
+    #[case(10, 2)]
+    #[case(2024, 4)]
+    #[case(u64::MAX, 20)]
+    fn test_digit_count(#[case] value: u64, #[case] expected: u32) {
+        assert_eq!(value.digit_count(), expected);
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(1234u64.split_digits(1), (123, 4));
+        assert_eq!(1234u64.split_digits(4), (0, 1234));
+        assert_eq!(255u8.split_digits(9), (0, 255));
+        assert_eq!(1000u64.split_in_half(), Some((10, 0)));
+        assert_eq!(253000u128.split_in_half(), Some((253, 0)));
+        assert_eq!(125u64.split_in_half(), None);
+        assert_eq!(0u64.split_in_half(), None);
+    }
+
+    #[test]
+    fn test_concat() {
+        assert_eq!(12u64.concat_digits(345), Some(12345));
+        assert_eq!(15u64.concat_digits(6), Some(156));
+        assert_eq!(1u64.concat_digits(0), Some(10));
+        assert_eq!(u64::MAX.concat_digits(1), None);
+    }
+
+    #[test]
+    fn test_to_digits() {
+        assert_eq!(0u32.to_digits(), vec![0]);
+        assert_eq!(2024u32.to_digits(), vec![2, 0, 2, 4]);
+    }
+}
@@ -0,0 +1,268 @@
+//! Euclid's algorithm and linear Diophantine equations.
+//!
+//! Inputs are `i64`; intermediate products are computed in `i128`, so the functions don't overflow
+//! for puzzle-sized numbers.
+
+/// Greatest common divisor, always non-negative. `gcd(0, 0)` is 0.
+pub fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    i64::try_from(a).expect("gcd doesn't fit in an i64")
+}
+
+/// Least common multiple, always non-negative. `lcm(0, x)` is 0.
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// Extended Euclidean algorithm.
+///
+/// Returns `(g, x, y)` such that `a*x + b*y == g`, where `g` is the (non-negative) gcd of `a` and
+/// `b`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a as i128, b as i128);
+    let (mut old_x, mut x) = (1i128, 0i128);
+    let (mut old_y, mut y) = (0i128, 1i128);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (old_r, old_x, old_y) = (-old_r, -old_x, -old_y);
+    }
+
+    (
+        i64::try_from(old_r).expect("gcd doesn't fit in an i64"),
+        old_x as i64,
+        old_y as i64,
+    )
+}
+
+/// Rounds the quotient towards negative infinity.
+pub(crate) fn div_floor(a: i128, b: i128) -> i128 {
+    let quotient = a / b;
+    if (a % b != 0) && ((a < 0) != (b < 0)) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+/// Rounds the quotient towards positive infinity.
+pub(crate) fn div_ceil(a: i128, b: i128) -> i128 {
+    -div_floor(-a, b)
+}
+
+/// All integer solutions of `a*x + b*y = c`.
+///
+/// The solutions are `(x0 + k*step_x, y0 - k*step_y)` for every integer `k`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinearDiophantine {
+    pub x0: i64,
+    pub y0: i64,
+    pub step_x: i64,
+    pub step_y: i64,
+}
+
+impl LinearDiophantine {
+    /// Solves `a*x + b*y = c`, returning `None` if there are no integer solutions.
+    ///
+    /// `a` and `b` must not both be zero, as the solutions can't be expressed in this form.
+    pub fn solve(a: i64, b: i64, c: i64) -> Option<Self> {
+        assert!(a != 0 || b != 0, "a and b must not both be zero");
+
+        let (g, x, _) = extended_gcd(a, b);
+        if c % g != 0 {
+            return None;
+        }
+
+        let step_x = (b / g) as i128;
+        let step_y = (a / g) as i128;
+
+        // Scale the Bézout coefficient, and pick the smallest non-negative x to keep numbers small.
+        let mut x0 = x as i128 * (c / g) as i128;
+        if step_x != 0 {
+            x0 = x0.rem_euclid(step_x.abs());
+        }
+        let y0 = if b != 0 {
+            (c as i128 - a as i128 * x0) / b as i128
+        } else {
+            // With b == 0, y can be anything; c / a is always exact here.
+            0
+        };
+
+        Some(Self {
+            x0: x0 as i64,
+            y0: y0 as i64,
+            step_x: step_x as i64,
+            step_y: step_y as i64,
+        })
+    }
+
+    /// The solution for parameter `k`.
+    pub fn at(&self, k: i64) -> (i64, i64) {
+        (self.x0 + k * self.step_x, self.y0 - k * self.step_y)
+    }
+
+    /// The range of `k` for which both `x` and `y` are non-negative, as inclusive (lower, upper)
+    /// bounds, where `None` means unbounded. Returns `None` if there's no such `k`.
+    pub fn non_negative_range(&self) -> Option<(Option<i64>, Option<i64>)> {
+        let mut lower: Option<i128> = None;
+        let mut upper: Option<i128> = None;
+
+        // Both constraints have the form v0 + k*s >= 0
+        for (v0, s) in [
+            (self.x0 as i128, self.step_x as i128),
+            (self.y0 as i128, -(self.step_y as i128)),
+        ] {
+            match s.signum() {
+                1 => lower = lower.max(Some(div_ceil(-v0, s))),
+                -1 => {
+                    let bound = div_floor(v0, -s);
+                    upper = Some(upper.map_or(bound, |u| u.min(bound)));
+                }
+                _ if v0 < 0 => return None,
+                _ => {}
+            }
+        }
+
+        if let (Some(l), Some(u)) = (lower, upper) {
+            if l > u {
+                return None;
+            }
+        }
+
+        Some((lower.map(|l| l as i64), upper.map(|u| u as i64)))
+    }
+
+    /// The non-negative solution with the lowest `cost_x*x + cost_y*y`.
+    ///
+    /// Costs must be non-negative, which guarantees a minimum exists. When several solutions
+    /// have the same cost, the one with the smallest `k` is returned.
+    pub fn min_cost_non_negative(&self, cost_x: i64, cost_y: i64) -> Option<(i64, i64)> {
+        assert!(cost_x >= 0 && cost_y >= 0, "costs must be non-negative");
+
+        let (lower, upper) = self.non_negative_range()?;
+
+        // The cost is linear in k, so the minimum is at one of the ends of the range
+        let slope = cost_x as i128 * self.step_x as i128 - cost_y as i128 * self.step_y as i128;
+        let k = match (lower, upper) {
+            (Some(l), _) if slope >= 0 => l,
+            (_, Some(u)) if slope <= 0 => u,
+            // Non-negative costs can't decrease indefinitely while x and y remain non-negative.
+            _ => unreachable!("unbounded cost with non-negative costs"),
+        };
+        Some(self.at(k))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(12, 18, 6)]
+    #[case(-12, 18, 6)]
+    #[case(0, 7, 7)]
+    #[case(0, 0, 0)]
+    #[case(17, 5, 1)]
+    fn test_gcd(#[case] a: i64, #[case] b: i64, #[case] expected: i64) {
+        assert_eq!(gcd(a, b), expected);
+        assert_eq!(extended_gcd(a, b).0, expected);
+    }
+
+    #[rstest]
+    #[case(4, 6, 12)]
+    #[case(-4, 6, 12)]
+    #[case(0, 6, 0)]
+    #[case(101, 103, 10403)]
+    fn test_lcm(#[case] a: i64, #[case] b: i64, #[case] expected: i64) {
+        assert_eq!(lcm(a, b), expected);
+    }
+
+    #[rstest]
+    #[case(240, 46)]
+    #[case(-240, 46)]
+    #[case(3, -7)]
+    #[case(0, 5)]
+    fn test_extended_gcd(#[case] a: i64, #[case] b: i64) {
+        let (g, x, y) = extended_gcd(a, b);
+        assert_eq!(a * x + b * y, g);
+    }
+
+    #[test]
+    fn test_div_rounding() {
+        assert_eq!(div_floor(7, 2), 3);
+        assert_eq!(div_floor(-7, 2), -4);
+        assert_eq!(div_floor(7, -2), -4);
+        assert_eq!(div_ceil(7, 2), 4);
+        assert_eq!(div_ceil(-7, 2), -3);
+        assert_eq!(div_ceil(6, 2), 3);
+    }
+
+    #[test]
+    fn test_diophantine() {
+        let solutions = LinearDiophantine::solve(6, 10, 104).unwrap();
+        for k in -5..5 {
+            let (x, y) = solutions.at(k);
+            assert_eq!(6 * x + 10 * y, 104);
+        }
+        assert_eq!(LinearDiophantine::solve(6, 10, 7), None);
+    }
+
+    #[test]
+    fn test_diophantine_degenerate() {
+        // b == 0: x is fixed, y is free
+        let solutions = LinearDiophantine::solve(4, 0, 12).unwrap();
+        assert_eq!(solutions.at(0).0, 3);
+        assert_eq!(solutions.at(7).0, 3);
+        assert_eq!(solutions.min_cost_non_negative(1, 1), Some((3, 0)));
+        assert_eq!(LinearDiophantine::solve(4, 0, 13), None);
+    }
+
+    #[test]
+    fn test_non_negative_range() {
+        // 3x + 5y = 30 has non-negative solutions (10, 0), (5, 3) and (0, 6)
+        let solutions = LinearDiophantine::solve(3, 5, 30).unwrap();
+        let (Some(lower), Some(upper)) = solutions.non_negative_range().unwrap() else {
+            panic!("range should be bounded");
+        };
+        let mut found = (lower..=upper).map(|k| solutions.at(k)).collect::<Vec<_>>();
+        found.sort();
+        assert_eq!(found, vec![(0, 6), (5, 3), (10, 0)]);
+
+        // 3x + 5y = 7 has no non-negative solutions
+        let solutions = LinearDiophantine::solve(3, 5, 7).unwrap();
+        assert_eq!(solutions.non_negative_range(), None);
+    }
+
+    #[rstest]
+    #[case::prefer_y(3, 5, 30, 3, 1, (0, 6))]
+    #[case::prefer_x(3, 5, 30, 1, 3, (10, 0))]
+    #[case::negative_coefficient(3, -5, 1, 1, 1, (2, 1))]
+    fn test_min_cost(
+        #[case] a: i64,
+        #[case] b: i64,
+        #[case] c: i64,
+        #[case] cost_x: i64,
+        #[case] cost_y: i64,
+        #[case] expected: (i64, i64),
+    ) {
+        let solutions = LinearDiophantine::solve(a, b, c).unwrap();
+        assert_eq!(
+            solutions.min_cost_non_negative(cost_x, cost_y),
+            Some(expected)
+        );
+    }
+}
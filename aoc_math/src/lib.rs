@@ -0,0 +1,12 @@
+pub mod cycle;
+pub mod digits;
+pub mod euclid;
+pub mod linalg;
+pub mod modular;
+
+// Main exports
+pub use cycle::{brent, detect_cycle, detect_cycle_by_key, fast_forward, floyd, Cycle};
+pub use digits::Digits;
+pub use euclid::{extended_gcd, gcd, lcm, LinearDiophantine};
+pub use linalg::{Rational, Solution};
+pub use modular::{crt, mod_inverse};
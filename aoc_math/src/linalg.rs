@@ -0,0 +1,270 @@
+//! Exact linear algebra for small integer systems.
+//!
+//! Systems are solved with Cramer's rule on `i128` determinants, so results are exact: a solution
+//! is either an integer, or a `Rational` that tells you it isn't. 3x3 determinants multiply three
+//! entries, so entries should stay below 2^42 in magnitude to avoid overflow.
+
+use std::fmt::Display;
+
+pub type Matrix2 = [[i64; 2]; 2];
+pub type Matrix3 = [[i64; 3]; 3];
+
+pub fn determinant_2x2(m: &Matrix2) -> i128 {
+    m[0][0] as i128 * m[1][1] as i128 - m[0][1] as i128 * m[1][0] as i128
+}
+
+pub fn determinant_3x3(m: &Matrix3) -> i128 {
+    let minor = |col_a: usize, col_b: usize| {
+        m[1][col_a] as i128 * m[2][col_b] as i128 - m[1][col_b] as i128 * m[2][col_a] as i128
+    };
+    m[0][0] as i128 * minor(1, 2) - m[0][1] as i128 * minor(0, 2) + m[0][2] as i128 * minor(0, 1)
+}
+
+/// An exact fraction, always stored in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        assert!(denominator != 0, "denominator must not be zero");
+        let divisor = gcd_i128(numerator, denominator) * denominator.signum();
+        Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    pub const fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub const fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub const fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    /// The value as an integer, if it is one and fits in an `i64`.
+    pub fn to_integer(&self) -> Option<i64> {
+        if self.is_integer() {
+            i64::try_from(self.numerator).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self {
+            numerator: value as i128,
+            denominator: 1,
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+fn gcd_i128(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The outcome of solving a linear system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solution<T> {
+    /// Exactly one solution.
+    Unique(T),
+    /// The equations contradict each other (or, for integer solving, the only solution isn't
+    /// integral).
+    Inconsistent,
+    /// The matrix is singular, but the equations agree: there are infinitely many (rational)
+    /// solutions. For integer systems, `LinearDiophantine` can enumerate them.
+    Infinite,
+}
+
+impl<T> Solution<T> {
+    pub fn unique(self) -> Option<T> {
+        match self {
+            Solution::Unique(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Rank of a small integer matrix, using fraction-free Gaussian elimination.
+fn rank<const R: usize, const C: usize>(mut m: [[i128; C]; R]) -> usize {
+    let mut rank = 0;
+    for col in 0..C {
+        let Some(pivot_row) = (rank..R).find(|&row| m[row][col] != 0) else {
+            continue;
+        };
+        m.swap(rank, pivot_row);
+
+        for row in rank + 1..R {
+            let factor = m[row][col];
+            let pivot_values = m[rank];
+            for (value, pivot_value) in m[row].iter_mut().zip(pivot_values) {
+                *value = *value * pivot_values[col] - pivot_value * factor;
+            }
+
+            // Keep the numbers small
+            let divisor = m[row].iter().fold(0, |acc, &v| gcd_i128(acc, v));
+            if divisor > 1 {
+                m[row].iter_mut().for_each(|v| *v /= divisor);
+            }
+        }
+        rank += 1;
+    }
+    rank
+}
+
+/// Distinguishes between no solutions and infinitely many solutions for a singular system.
+fn singular_solution<T, const N: usize, const C: usize>(
+    matrix: [[i64; N]; N],
+    rhs: [i64; N],
+) -> Solution<T> {
+    let coefficients = matrix.map(|row| row.map(|v| v as i128));
+    let mut augmented = [[0i128; C]; N];
+    for (row, augmented_row) in augmented.iter_mut().enumerate() {
+        augmented_row[..N].copy_from_slice(&coefficients[row]);
+        augmented_row[N] = rhs[row] as i128;
+    }
+
+    if rank(coefficients) == rank(augmented) {
+        Solution::Infinite
+    } else {
+        Solution::Inconsistent
+    }
+}
+
+/// Replaces column `col` of the matrix with the right hand side, as used by Cramer's rule.
+fn replace_column<const N: usize>(
+    mut matrix: [[i64; N]; N],
+    col: usize,
+    rhs: [i64; N],
+) -> [[i64; N]; N] {
+    for (row, value) in rhs.iter().enumerate() {
+        matrix[row][col] = *value;
+    }
+    matrix
+}
+
+/// Solves `m * [x, y] = rhs` exactly.
+pub fn solve_2x2(m: &Matrix2, rhs: [i64; 2]) -> Solution<[Rational; 2]> {
+    let det = determinant_2x2(m);
+    if det == 0 {
+        return singular_solution::<_, 2, 3>(*m, rhs);
+    }
+    Solution::Unique(std::array::from_fn(|col| {
+        Rational::new(determinant_2x2(&replace_column(*m, col, rhs)), det)
+    }))
+}
+
+/// Solves `m * [x, y, z] = rhs` exactly.
+pub fn solve_3x3(m: &Matrix3, rhs: [i64; 3]) -> Solution<[Rational; 3]> {
+    let det = determinant_3x3(m);
+    if det == 0 {
+        return singular_solution::<_, 3, 4>(*m, rhs);
+    }
+    Solution::Unique(std::array::from_fn(|col| {
+        Rational::new(determinant_3x3(&replace_column(*m, col, rhs)), det)
+    }))
+}
+
+fn integer_solution<const N: usize>(solution: Solution<[Rational; N]>) -> Solution<[i64; N]> {
+    match solution {
+        Solution::Unique(values) => {
+            if values.iter().all(Rational::is_integer) {
+                Solution::Unique(values.map(|v| v.to_integer().expect("solution overflows i64")))
+            } else {
+                Solution::Inconsistent
+            }
+        }
+        Solution::Inconsistent => Solution::Inconsistent,
+        Solution::Infinite => Solution::Infinite,
+    }
+}
+
+/// Solves `m * [x, y] = rhs`, where only integer solutions are accepted.
+pub fn solve_integer_2x2(m: &Matrix2, rhs: [i64; 2]) -> Solution<[i64; 2]> {
+    integer_solution(solve_2x2(m, rhs))
+}
+
+/// Solves `m * [x, y, z] = rhs`, where only integer solutions are accepted.
+pub fn solve_integer_3x3(m: &Matrix3, rhs: [i64; 3]) -> Solution<[i64; 3]> {
+    integer_solution(solve_3x3(m, rhs))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_determinants() {
+        assert_eq!(determinant_2x2(&[[3, 8], [4, 6]]), -14);
+        assert_eq!(determinant_3x3(&[[6, 1, 1], [4, -2, 5], [2, 8, 7]]), -306);
+        assert_eq!(determinant_3x3(&[[1, 2, 3], [4, 5, 6], [7, 8, 9]]), 0);
+    }
+
+    #[test]
+    fn test_rational() {
+        let r = Rational::new(6, -4);
+        assert_eq!((r.numerator(), r.denominator()), (-3, 2));
+        assert_eq!(r.to_string(), "-3/2");
+        assert_eq!(r.to_integer(), None);
+        assert_eq!(Rational::new(-8, -4).to_integer(), Some(2));
+        assert_eq!(Rational::new(0, -4), Rational::from(0));
+    }
+
+    #[rstest]
+    #[case::unique([[94, 22], [34, 67]], [8400, 5400], Solution::Unique([80, 40]))]
+    #[case::not_integer([[26, 67], [66, 21]], [12748, 12176], Solution::Inconsistent)]
+    #[case::collinear([[1, 2], [2, 4]], [3, 6], Solution::Infinite)]
+    #[case::parallel([[1, 2], [2, 4]], [3, 7], Solution::Inconsistent)]
+    #[case::zero_matrix([[0, 0], [0, 0]], [0, 0], Solution::Infinite)]
+    #[case::zero_matrix_inconsistent([[0, 0], [0, 0]], [0, 1], Solution::Inconsistent)]
+    fn test_solve_integer_2x2(
+        #[case] m: Matrix2,
+        #[case] rhs: [i64; 2],
+        #[case] expected: Solution<[i64; 2]>,
+    ) {
+        assert_eq!(solve_integer_2x2(&m, rhs), expected);
+    }
+
+    #[test]
+    fn test_solve_2x2_rational() {
+        let solution = solve_2x2(&[[2, 0], [0, 4]], [1, 2]).unique().unwrap();
+        assert_eq!(solution, [Rational::new(1, 2), Rational::new(1, 2)]);
+    }
+
+    #[rstest]
+    #[case::unique([[2, 1, -1], [-3, -1, 2], [-2, 1, 2]], [8, -11, -3], Solution::Unique([2, 3, -1]))]
+    #[case::dependent([[1, 2, 3], [4, 5, 6], [7, 8, 9]], [6, 15, 24], Solution::Infinite)]
+    #[case::inconsistent([[1, 2, 3], [4, 5, 6], [7, 8, 9]], [6, 15, 25], Solution::Inconsistent)]
+    fn test_solve_integer_3x3(
+        #[case] m: Matrix3,
+        #[case] rhs: [i64; 3],
+        #[case] expected: Solution<[i64; 3]>,
+    ) {
+        assert_eq!(solve_integer_3x3(&m, rhs), expected);
+    }
+}
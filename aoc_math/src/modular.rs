@@ -0,0 +1,92 @@
+//! Modular arithmetic: inverses and the Chinese Remainder Theorem.
+
+use crate::euclid::{extended_gcd, gcd};
+
+/// The inverse of `a` modulo `modulus`, in `0..modulus`. Only exists if `a` and `modulus` are
+/// coprime.
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    assert!(modulus > 0, "modulus must be positive");
+
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(modulus))
+}
+
+/// Solves a system of congruences `x ≡ residue (mod modulus)` with the Chinese Remainder Theorem.
+///
+/// The moduli don't need to be coprime. Returns `(x, lcm)`, where `x` is the smallest
+/// non-negative solution and every solution is `x + k*lcm`, or `None` if the congruences
+/// contradict each other. Panics if the combined modulus doesn't fit in an `i64`.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    congruences.iter().try_fold(
+        (0i64, 1i64),
+        |(residue, modulus), &(next_residue, next_modulus)| {
+            assert!(next_modulus > 0, "modulus must be positive");
+            combine(
+                residue,
+                modulus,
+                next_residue.rem_euclid(next_modulus),
+                next_modulus,
+            )
+        },
+    )
+}
+
+/// Merges two congruences into one.
+fn combine(r1: i64, m1: i64, r2: i64, m2: i64) -> Option<(i64, i64)> {
+    let g = gcd(m1, m2);
+    if (r2 - r1) % g != 0 {
+        return None;
+    }
+
+    // m1*p + m2*q = g, so m1*p ≡ g (mod m2)
+    let (_, p, _) = extended_gcd(m1, m2);
+    let reduced_modulus = (m2 / g) as i128;
+    let steps = ((r2 - r1) / g) as i128 * p as i128 % reduced_modulus;
+
+    let lcm = m1 as i128 * reduced_modulus;
+    let x = (r1 as i128 + m1 as i128 * steps).rem_euclid(lcm);
+
+    Some((
+        i64::try_from(x).expect("solution doesn't fit in an i64"),
+        i64::try_from(lcm).expect("combined modulus doesn't fit in an i64"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(3, 11, Some(4))]
+    #[case(10, 17, Some(12))]
+    #[case(-3, 11, Some(7))]
+    #[case(6, 9, None)]
+    fn test_mod_inverse(#[case] a: i64, #[case] modulus: i64, #[case] expected: Option<i64>) {
+        assert_eq!(mod_inverse(a, modulus), expected);
+    }
+
+    #[rstest]
+    #[case::coprime(&[(2, 3), (3, 5), (2, 7)], Some((23, 105)))]
+    #[case::non_coprime(&[(3, 4), (5, 6)], Some((11, 12)))]
+    #[case::contradiction(&[(1, 4), (2, 6)], None)]
+    #[case::negative_residue(&[(-1, 101), (-1, 103)], Some((10402, 10403)))]
+    #[case::empty(&[], Some((0, 1)))]
+    fn test_crt(#[case] congruences: &[(i64, i64)], #[case] expected: Option<(i64, i64)>) {
+        assert_eq!(crt(congruences), expected);
+    }
+
+    #[test]
+    fn test_crt_large() {
+        let moduli = [1_000_003, 999_983, 1_000_033];
+        let x = 123_456_789_012_345i64;
+        let congruences = moduli.map(|m| (x % m, m));
+        let (solution, modulus) = crt(&congruences).unwrap();
+        assert_eq!(modulus, moduli.iter().product::<i64>());
+        assert_eq!(solution, x % modulus);
+    }
+}
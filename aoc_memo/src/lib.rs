@@ -0,0 +1,4 @@
+pub mod memo;
+
+// Main exports
+pub use memo::{Memo, Stats};
@@ -0,0 +1,206 @@
+//! An explicit memoization cache.
+//!
+//! Unlike attribute macros that memoize into a hidden global, a `Memo` is an ordinary value: create
+//! one per solve, pass it down the recursion, and drop it when done. This keeps benchmark
+//! iterations independent, and stops results from one input leaking into the next.
+//!
+//! ```
+//! use aoc_memo::Memo;
+//!
+//! fn fibonacci(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+//!     if n < 2 {
+//!         return n;
+//!     }
+//!     memo.get_or_compute(n, |memo| fibonacci(memo, n - 1) + fibonacci(memo, n - 2))
+//! }
+//!
+//! let mut memo = Memo::new();
+//! assert_eq!(fibonacci(&mut memo, 90), 2880067194370816120);
+//! ```
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use rustc_hash::FxBuildHasher;
+
+/// Cache hit and miss counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Results that were computed, but not stored because the cache was full.
+    pub dropped: u64,
+}
+
+impl Stats {
+    /// The fraction of lookups that were answered from the cache, between 0 and 1.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A memoization cache from `K` to `V`. Uses the fast (but not DoS-resistant) Fx hasher by
+/// default; any other hasher can be used with `with_hasher`.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V, S = FxBuildHasher> {
+    cache: HashMap<K, V, S>,
+    limit: Option<usize>,
+    stats: Stats,
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(FxBuildHasher)
+    }
+
+    /// Creates a cache that can hold `capacity` results before it needs to grow.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            cache: HashMap::with_capacity_and_hasher(capacity, FxBuildHasher),
+            limit: None,
+            stats: Stats::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V: Clone, S: BuildHasher> Memo<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            cache: HashMap::with_hasher(hasher),
+            limit: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Stops storing new results once the cache holds `limit` entries. Stored results are kept,
+    /// so the cache stays useful for the (typically hot) states that were seen first.
+    #[must_use]
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns the cached result for `key`, or computes and caches it.
+    ///
+    /// `compute` receives the cache itself, so recursive functions can use it for their
+    /// sub-problems.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+
+        self.stats.misses += 1;
+        let value = compute(self);
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Looks up a cached result, without counting it as a hit or miss.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    /// Stores a result, unless the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        if self
+            .limit
+            .is_some_and(|limit| self.cache.len() >= limit && !self.cache.contains_key(&key))
+        {
+            self.stats.dropped += 1;
+            return;
+        }
+        self.cache.insert(key, value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Removes all cached results and resets the statistics.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.stats = Stats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn fibonacci<S: BuildHasher>(memo: &mut Memo<u64, u64, S>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_compute(n, |memo| fibonacci(memo, n - 1) + fibonacci(memo, n - 2))
+    }
+
+    #[test]
+    fn test_recursion() {
+        let mut memo = Memo::with_capacity(100);
+        assert_eq!(fibonacci(&mut memo, 50), 12586269025);
+        assert_eq!(memo.len(), 49);
+        assert_eq!(
+            memo.stats(),
+            Stats {
+                hits: 47,
+                misses: 49,
+                dropped: 0
+            }
+        );
+
+        // Everything is cached now
+        assert_eq!(fibonacci(&mut memo, 50), 12586269025);
+        assert_eq!(memo.stats().hits, 48);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut memo = Memo::new();
+        fibonacci(&mut memo, 10);
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(memo.stats(), Stats::default());
+        assert_eq!(memo.stats().hit_rate(), 0.0);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(5)]
+    #[case(1000)]
+    fn test_limit(#[case] limit: usize) {
+        let mut memo = Memo::new().with_limit(limit);
+        assert_eq!(fibonacci(&mut memo, 30), 832040);
+        assert!(memo.len() <= limit);
+        // fibonacci(2..=30) are cached when there's room
+        assert_eq!(memo.len(), limit.min(29));
+        assert_eq!(memo.stats().dropped > 0, limit < 29);
+    }
+
+    #[test]
+    fn test_std_hasher() {
+        let mut memo = Memo::with_hasher(std::collections::hash_map::RandomState::new());
+        assert_eq!(fibonacci(&mut memo, 20), 6765);
+    }
+}
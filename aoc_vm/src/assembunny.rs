@@ -0,0 +1,230 @@
+//! Assembunny, the machine from Advent of Code 2016 (days 12 and 25): four registers `a` to `d`
+//! and relative jumps.
+
+use std::fmt::Display;
+
+use crate::machine::{Effect, InstructionSet};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Value {
+    Register(usize),
+    Literal(i64),
+}
+
+impl Value {
+    fn get(&self, registers: &[i64]) -> i64 {
+        match self {
+            Value::Register(index) => registers[*index],
+            Value::Literal(value) => *value,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Register(index) => write!(f, "{}", Assembunny::register_name(*index)),
+            Value::Literal(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Instruction {
+    Cpy(Value, usize),
+    Inc(usize),
+    Dec(usize),
+    Jnz(Value, Value),
+    Out(Value),
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = Assembunny::register_name;
+        match self {
+            Instruction::Cpy(value, register) => write!(f, "cpy {value} {}", name(*register)),
+            Instruction::Inc(register) => write!(f, "inc {}", name(*register)),
+            Instruction::Dec(register) => write!(f, "dec {}", name(*register)),
+            Instruction::Jnz(value, offset) => write!(f, "jnz {value} {offset}"),
+            Instruction::Out(value) => write!(f, "out {value}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Assembunny;
+
+impl InstructionSet for Assembunny {
+    type Word = i64;
+    type Instruction = Instruction;
+    type Output = i64;
+
+    fn execute(instruction: &Instruction, registers: &mut [i64], pc: usize) -> Effect<i64> {
+        match instruction {
+            Instruction::Cpy(value, register) => registers[*register] = value.get(registers),
+            Instruction::Inc(register) => registers[*register] += 1,
+            Instruction::Dec(register) => registers[*register] -= 1,
+            Instruction::Jnz(value, offset) if value.get(registers) != 0 => {
+                return match usize::try_from(pc as i64 + offset.get(registers)) {
+                    Ok(target) => Effect::Jump(target),
+                    // Jumping before the start of the program halts it
+                    Err(_) => Effect::Halt,
+                };
+            }
+            Instruction::Jnz(..) => {}
+            Instruction::Out(value) => return Effect::Output(value.get(registers)),
+        }
+        Effect::Next
+    }
+
+    fn register_name(index: usize) -> String {
+        char::from(b'a' + index as u8).to_string()
+    }
+}
+
+fn parse_register(word: &str) -> Result<usize, String> {
+    match word {
+        "a" | "b" | "c" | "d" => Ok((word.as_bytes()[0] - b'a') as usize),
+        _ => Err(format!("unknown register {word}")),
+    }
+}
+
+fn parse_value(word: &str) -> Result<Value, String> {
+    match word.parse() {
+        Ok(value) => Ok(Value::Literal(value)),
+        Err(_) => parse_register(word).map(Value::Register),
+    }
+}
+
+fn parse_instruction(line: &str) -> Result<Instruction, String> {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    Ok(match words[..] {
+        ["cpy", value, register] => {
+            Instruction::Cpy(parse_value(value)?, parse_register(register)?)
+        }
+        ["inc", register] => Instruction::Inc(parse_register(register)?),
+        ["dec", register] => Instruction::Dec(parse_register(register)?),
+        ["jnz", value, offset] => Instruction::Jnz(parse_value(value)?, parse_value(offset)?),
+        ["out", value] => Instruction::Out(parse_value(value)?),
+        _ => return Err(format!("invalid instruction '{line}'")),
+    })
+}
+
+/// Parses a program, one instruction per line. Blank lines are ignored.
+pub fn parse(source: &str) -> Result<Vec<Instruction>, String> {
+    source
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(number, line)| {
+            parse_instruction(line).map_err(|e| format!("line {}: {e}", number + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::machine::{Machine, TraceEntry};
+
+    // The example from 2016 day 12
+    const EXAMPLE: &str = "
+        cpy 41 a
+        inc a
+        inc a
+        dec a
+        jnz a 2
+        dec a
+    ";
+
+    // Outputs a, a - 1, ..., 1
+    const COUNTDOWN: &str = "
+        out a
+        dec a
+        jnz a -2
+    ";
+
+    #[test]
+    fn test_run() {
+        let program = parse(EXAMPLE).unwrap();
+        let mut vm = Machine::<Assembunny, 4>::new([0; 4], &program);
+        vm.run();
+        assert_eq!(vm.registers, [42, 0, 0, 0]);
+        assert_eq!(vm.cycles, 5);
+        assert!(vm.is_halted());
+    }
+
+    #[test]
+    fn test_output() {
+        let program = parse(COUNTDOWN).unwrap();
+        let vm = Machine::<Assembunny, 4>::new([3, 0, 0, 0], &program);
+        assert_eq!(vm.collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_trace() {
+        let program = parse(COUNTDOWN).unwrap();
+        let mut vm = Machine::<Assembunny, 4>::new([2, 0, 0, 0], &program).with_trace();
+        vm.run();
+
+        let trace = vm.trace().unwrap();
+        assert_eq!(trace.len(), 6);
+        assert_eq!(
+            trace[1],
+            TraceEntry {
+                cycle: 1,
+                pc: 1,
+                instruction: Instruction::Dec(0),
+                before: [2, 0, 0, 0],
+                after: [1, 0, 0, 0],
+                output: None,
+            }
+        );
+        assert_eq!(
+            trace[3].to_string(),
+            "#3    00: out a  a=1 b=0 c=0 d=0 -> a=1 b=0 c=0 d=0 out 1"
+        );
+    }
+
+    #[test]
+    fn test_profile_and_limit() {
+        // Never terminates
+        let program = parse("inc b\njnz 1 -1").unwrap();
+        let mut vm = Machine::<Assembunny, 4>::new([0; 4], &program)
+            .with_instruction_limit(100)
+            .with_profile();
+        assert_eq!(vm.next(), None);
+        assert!(vm.limit_reached());
+        assert_eq!(vm.profile(), Some(&[50, 50][..]));
+        assert_eq!(vm.registers[1], 50);
+    }
+
+    #[test]
+    fn test_jump_before_start() {
+        let program = parse("inc a\njnz a -5\ninc a").unwrap();
+        let mut vm = Machine::<Assembunny, 4>::new([0; 4], &program);
+        vm.run();
+        assert!(vm.is_halted());
+        assert_eq!(vm.registers[0], 1);
+    }
+
+    #[rstest]
+    #[case::unknown_instruction("tgl a", "line 1: invalid instruction 'tgl a'")]
+    #[case::missing_operand("inc a\n\ncpy 1", "line 3: invalid instruction 'cpy 1'")]
+    #[case::unknown_register("inc e", "line 1: unknown register e")]
+    #[case::literal_target("cpy a 1", "line 1: unknown register 1")]
+    fn test_parse_errors(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(parse(source), Err(expected.to_string()));
+    }
+
+    #[test]
+    fn test_display() {
+        let program = parse(EXAMPLE).unwrap();
+        let listing = program.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(listing[0], "cpy 41 a");
+        assert_eq!(listing[4], "jnz a 2");
+    }
+}
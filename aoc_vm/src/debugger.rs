@@ -0,0 +1,409 @@
+//! An interactive step debugger for any `Machine`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+
+use crate::machine::{InstructionSet, Machine};
+use crate::word::Word;
+
+// Stops `continue` on programs that never halt and have no breakpoints.
+const CONTINUE_LIMIT: usize = 10_000_000;
+
+const HELP: &str = "\
+commands:
+  s, step [n]        execute n instructions (default 1)
+  c, continue        run until a breakpoint, watchpoint or the end of the program
+  b, break <addr>    set a breakpoint at an address
+  d, delete <addr>   remove a breakpoint
+  w, watch <reg>     stop when a register changes
+  u, unwatch <reg>   remove a watchpoint
+  r, regs            show the registers
+  o, output          show the output so far
+  l, list            show the program, with the current instruction marked
+  radix <dec|oct|bin>  change how registers are shown
+  h, help            show this help
+  q, quit            leave the debugger";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Radix {
+    Decimal,
+    Octal,
+    Binary,
+}
+
+impl Radix {
+    pub fn format<W: Word>(&self, value: W) -> String {
+        match self {
+            Radix::Decimal => value.to_string(),
+            Radix::Octal => format!("{value:#o}"),
+            Radix::Binary => format!("{value:#b}"),
+        }
+    }
+}
+
+/// A step debugger around a `Machine`. Addresses are the ones the instruction set shows, see
+/// `InstructionSet::address`.
+pub struct Debugger<'a, I: InstructionSet, const N: usize> {
+    pub vm: Machine<'a, I, N>,
+    pub breakpoints: BTreeSet<usize>,
+    pub watchpoints: [bool; N],
+    pub output: Vec<I::Output>,
+    pub radix: Radix,
+    // Names for addresses, shown in the listing
+    labels: BTreeMap<usize, String>,
+}
+
+// Why execution stopped
+enum Stop<W> {
+    Halted,
+    Breakpoint,
+    Watchpoint(usize, W),
+    Steps,
+    Limit,
+}
+
+impl<'a, I: InstructionSet, const N: usize> Debugger<'a, I, N> {
+    pub fn new(registers: [I::Word; N], program: &'a [I::Instruction]) -> Self {
+        Self {
+            vm: Machine::new(registers, program),
+            breakpoints: BTreeSet::new(),
+            watchpoints: [false; N],
+            output: Vec::new(),
+            radix: Radix::Decimal,
+            labels: BTreeMap::new(),
+        }
+    }
+
+    /// Shows `label:` above the instruction at each address in the listing.
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = (usize, String)>) -> Self {
+        self.labels.extend(labels);
+        self
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.vm.is_halted()
+    }
+
+    fn address(&self) -> usize {
+        I::address(self.vm.pc)
+    }
+
+    // Executes a single instruction, reporting a watchpoint if it changed a watched register
+    fn step(&mut self) -> Option<Stop<I::Word>> {
+        let before = self.vm.registers;
+        if let Some(value) = self.vm.run_step() {
+            self.output.push(value);
+        }
+        (0..N)
+            .find(|&r| self.watchpoints[r] && self.vm.registers[r] != before[r])
+            .map(|r| Stop::Watchpoint(r, before[r]))
+    }
+
+    fn run(&mut self, steps: Option<usize>) -> Stop<I::Word> {
+        let limit = steps.unwrap_or(CONTINUE_LIMIT);
+        for count in 0..limit {
+            if self.is_halted() {
+                return Stop::Halted;
+            }
+            if count > 0 && steps.is_none() && self.breakpoints.contains(&self.address()) {
+                return Stop::Breakpoint;
+            }
+            if let Some(stop) = self.step() {
+                return stop;
+            }
+        }
+        if self.is_halted() {
+            Stop::Halted
+        } else if steps.is_some() {
+            Stop::Steps
+        } else {
+            Stop::Limit
+        }
+    }
+
+    fn describe_stop(&self, stop: Stop<I::Word>) -> String {
+        let location = self.location();
+        match stop {
+            Stop::Halted => "program halted".to_string(),
+            Stop::Breakpoint => format!("breakpoint at {location}"),
+            Stop::Watchpoint(r, before) => format!(
+                "watchpoint: {} changed from {} to {}, now at {location}",
+                I::register_name(r),
+                self.radix.format(before),
+                self.radix.format(self.vm.registers[r])
+            ),
+            Stop::Steps => location,
+            Stop::Limit => format!("stopped after {CONTINUE_LIMIT} instructions at {location}"),
+        }
+    }
+
+    fn location(&self) -> String {
+        match self.vm.program.get(self.vm.pc) {
+            Some(instruction) => format!("{:02}: {instruction}", self.address()),
+            None => "end of program".to_string(),
+        }
+    }
+
+    pub fn registers(&self) -> String {
+        (0..N)
+            .map(|r| {
+                format!(
+                    "{}={}",
+                    I::register_name(r),
+                    self.radix.format(self.vm.registers[r])
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn listing(&self) -> String {
+        let mut lines = Vec::new();
+        for (pc, instruction) in self.vm.program.iter().enumerate() {
+            let address = I::address(pc);
+            if let Some(label) = self.labels.get(&address) {
+                lines.push(format!(" {label}:"));
+            }
+            let marker = if pc == self.vm.pc {
+                '>'
+            } else if self.breakpoints.contains(&address) {
+                '*'
+            } else {
+                ' '
+            };
+            lines.push(format!("{marker}   {address:02}: {instruction}"));
+        }
+        lines.join("\n")
+    }
+
+    fn parse_address(&self, argument: Option<&str>) -> Result<usize, String> {
+        let address = argument
+            .ok_or("missing address")?
+            .parse::<usize>()
+            .map_err(|_| "invalid address")?;
+        if !(0..self.vm.program.len()).any(|pc| I::address(pc) == address) {
+            return Err(format!("no instruction at address {address}"));
+        }
+        Ok(address)
+    }
+
+    fn parse_register(argument: Option<&str>) -> Result<usize, String> {
+        let name = argument.ok_or("missing register")?;
+        (0..N)
+            .find(|&r| I::register_name(r).eq_ignore_ascii_case(name))
+            .ok_or(format!("unknown register {name}"))
+    }
+
+    /// Executes a single debugger command, returning the text to show.
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("step");
+        let argument = words.next();
+
+        match command {
+            "s" | "step" => {
+                let count = match argument {
+                    Some(count) => count.parse().map_err(|_| "invalid step count")?,
+                    None => 1,
+                };
+                let stop = self.run(Some(count));
+                Ok(format!(
+                    "{}\n{}",
+                    self.describe_stop(stop),
+                    self.registers()
+                ))
+            }
+            "c" | "continue" => {
+                let stop = self.run(None);
+                Ok(format!(
+                    "{}\n{}",
+                    self.describe_stop(stop),
+                    self.registers()
+                ))
+            }
+            "b" | "break" => {
+                let address = self.parse_address(argument)?;
+                self.breakpoints.insert(address);
+                Ok(format!("breakpoint set at {address:02}"))
+            }
+            "d" | "delete" => {
+                let address = self.parse_address(argument)?;
+                if self.breakpoints.remove(&address) {
+                    Ok(format!("breakpoint at {address:02} removed"))
+                } else {
+                    Err(format!("no breakpoint at {address:02}"))
+                }
+            }
+            "w" | "watch" => {
+                let register = Self::parse_register(argument)?;
+                self.watchpoints[register] = true;
+                Ok(format!("watching {}", I::register_name(register)))
+            }
+            "u" | "unwatch" => {
+                let register = Self::parse_register(argument)?;
+                self.watchpoints[register] = false;
+                Ok(format!("no longer watching {}", I::register_name(register)))
+            }
+            "r" | "regs" => Ok(self.registers()),
+            "o" | "output" => Ok(self
+                .output
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(",")),
+            "l" | "list" => Ok(self.listing()),
+            "radix" => {
+                self.radix = match argument {
+                    Some("dec") => Radix::Decimal,
+                    Some("oct") => Radix::Octal,
+                    Some("bin") => Radix::Binary,
+                    _ => return Err("radix must be dec, oct or bin".to_string()),
+                };
+                Ok(self.registers())
+            }
+            "h" | "help" => Ok(HELP.to_string()),
+            _ => Err(format!("unknown command {command}, try 'help'")),
+        }
+    }
+
+    /// Reads commands until `quit` or the end of the input. An empty line repeats a single step.
+    pub fn run_interactive(
+        &mut self,
+        input: impl BufRead,
+        mut output: impl Write,
+    ) -> std::io::Result<()> {
+        writeln!(output, "{}\n{}", self.location(), self.registers())?;
+        write!(output, "(debug) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            if matches!(line.trim(), "q" | "quit") {
+                break;
+            }
+            match self.execute(&line) {
+                Ok(text) => writeln!(output, "{text}")?,
+                Err(error) => writeln!(output, "error: {error}")?,
+            }
+            write!(output, "(debug) ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::assembunny::{parse, Assembunny, Instruction};
+
+    // Outputs a, a - 1, ..., 1
+    const COUNTDOWN: &str = "
+        out a
+        dec a
+        jnz a -2
+    ";
+
+    fn countdown() -> Vec<Instruction> {
+        parse(COUNTDOWN).unwrap()
+    }
+
+    #[rstest]
+    #[case(Radix::Decimal, 729, "729")]
+    #[case(Radix::Octal, 729, "0o1331")]
+    #[case(Radix::Binary, 5, "0b101")]
+    fn test_radix(#[case] radix: Radix, #[case] value: i64, #[case] expected: &str) {
+        assert_eq!(radix.format(value), expected);
+        assert_eq!(radix.format(value as u128), expected);
+    }
+
+    #[test]
+    fn test_step() {
+        let program = countdown();
+        let mut debugger = Debugger::<Assembunny, 4>::new([3, 0, 0, 0], &program);
+
+        assert_eq!(
+            debugger.execute("step").unwrap(),
+            "01: dec a\na=3 b=0 c=0 d=0"
+        );
+        assert_eq!(
+            debugger.execute("s 2").unwrap(),
+            "00: out a\na=2 b=0 c=0 d=0"
+        );
+        assert_eq!(debugger.execute("output").unwrap(), "3");
+        assert_eq!(
+            debugger.execute("s 100").unwrap(),
+            "program halted\na=0 b=0 c=0 d=0"
+        );
+        assert_eq!(debugger.execute("output").unwrap(), "3,2,1");
+    }
+
+    #[test]
+    fn test_breakpoint() {
+        let program = countdown();
+        let mut debugger = Debugger::<Assembunny, 4>::new([3, 0, 0, 0], &program);
+
+        debugger.execute("break 2").unwrap();
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "breakpoint at 02: jnz a -2\na=2 b=0 c=0 d=0"
+        );
+        // Continuing from a breakpoint doesn't stop at the same breakpoint immediately
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "breakpoint at 02: jnz a -2\na=1 b=0 c=0 d=0"
+        );
+        debugger.execute("delete 2").unwrap();
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "program halted\na=0 b=0 c=0 d=0"
+        );
+        assert!(debugger.execute("break 3").is_err());
+        assert!(debugger.execute("delete 2").is_err());
+    }
+
+    #[test]
+    fn test_watchpoint() {
+        let program = parse("inc b\ninc b\ncpy b c\ninc b").unwrap();
+        let mut debugger = Debugger::<Assembunny, 4>::new([0; 4], &program);
+
+        debugger.execute("watch C").unwrap();
+        debugger.execute("radix bin").unwrap();
+        assert_eq!(
+            debugger.execute("continue").unwrap(),
+            "watchpoint: c changed from 0b0 to 0b10, now at 03: inc b\na=0b0 b=0b10 c=0b10 d=0b0"
+        );
+        assert_eq!(
+            debugger.execute("watch e"),
+            Err("unknown register e".to_string())
+        );
+    }
+
+    #[test]
+    fn test_interactive() {
+        let program = countdown();
+        let mut debugger = Debugger::<Assembunny, 4>::new([3, 0, 0, 0], &program)
+            .with_labels([(0, "loop".to_string())]);
+        debugger.execute("break 2").unwrap();
+
+        let mut screen = Vec::new();
+        debugger
+            .run_interactive("\nlist\nfoo\nquit\nstep\n".as_bytes(), &mut screen)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(screen).unwrap(),
+            "00: out a
+a=3 b=0 c=0 d=0
+(debug) 01: dec a
+a=3 b=0 c=0 d=0
+(debug)  loop:
+    00: out a
+>   01: dec a
+*   02: jnz a -2
+(debug) error: unknown command foo, try 'help'
+(debug) "
+        );
+    }
+}
@@ -0,0 +1,9 @@
+pub mod assembunny;
+pub mod debugger;
+pub mod machine;
+pub mod word;
+
+// Main exports
+pub use debugger::{Debugger, Radix};
+pub use machine::{Effect, InstructionSet, Machine, TraceEntry};
+pub use word::Word;
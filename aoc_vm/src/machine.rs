@@ -0,0 +1,212 @@
+//! A register machine, generic over its instruction set and word type.
+//!
+//! The machine owns the run loop: the program counter, the cycle counter, the instruction limit,
+//! tracing and profiling. An `InstructionSet` only has to say what a single instruction does to
+//! the registers, so machines from different puzzles share everything else, including the
+//! debugger.
+
+use std::fmt::{Debug, Display};
+
+use crate::word::Word;
+
+/// What an instruction does to the control flow.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Effect<O> {
+    /// Continue with the next instruction.
+    Next,
+    /// Continue at an instruction index. Jumping outside of the program halts it.
+    Jump(usize),
+    /// Produce output, and continue with the next instruction.
+    Output(O),
+    /// Stop the program.
+    Halt,
+}
+
+pub trait InstructionSet {
+    type Word: Word;
+    type Instruction: Clone + Debug + Display;
+    type Output: Clone + Debug + PartialEq + Display;
+
+    /// Executes `instruction`, found at instruction index `pc`.
+    fn execute(
+        instruction: &Self::Instruction,
+        registers: &mut [Self::Word],
+        pc: usize,
+    ) -> Effect<Self::Output>;
+
+    /// The address of the instruction at index `pc`, as shown in traces and in the debugger.
+    fn address(pc: usize) -> usize {
+        pc
+    }
+
+    /// The name of a register, as shown in traces and in the debugger.
+    fn register_name(index: usize) -> String {
+        char::from(b'A' + index as u8).to_string()
+    }
+}
+
+/// One executed instruction, as recorded by a tracing `Machine`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceEntry<I: InstructionSet, const N: usize> {
+    pub cycle: usize,
+    pub pc: usize,
+    pub instruction: I::Instruction,
+    pub before: [I::Word; N],
+    pub after: [I::Word; N],
+    pub output: Option<I::Output>,
+}
+
+fn format_registers<I: InstructionSet>(registers: &[I::Word]) -> String {
+    registers
+        .iter()
+        .enumerate()
+        .map(|(index, value)| format!("{}={value}", I::register_name(index)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl<I: InstructionSet, const N: usize> Display for TraceEntry<I, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{:<4} {:02}: {:<6} {} -> {}",
+            self.cycle,
+            I::address(self.pc),
+            self.instruction.to_string(),
+            format_registers::<I>(&self.before),
+            format_registers::<I>(&self.after)
+        )?;
+        if let Some(output) = &self.output {
+            write!(f, " out {output}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a program of `I` instructions on `N` registers. As an iterator, it yields the output.
+pub struct Machine<'a, I: InstructionSet, const N: usize> {
+    pub registers: [I::Word; N],
+    pub program: &'a [I::Instruction],
+    pub pc: usize,
+    // The number of instructions executed so far
+    pub cycles: usize,
+    instruction_limit: Option<usize>,
+    trace: Option<Vec<TraceEntry<I, N>>>,
+    profile: Option<Vec<usize>>,
+}
+
+impl<'a, I: InstructionSet, const N: usize> Machine<'a, I, N> {
+    pub fn new(registers: [I::Word; N], program: &'a [I::Instruction]) -> Self {
+        Machine {
+            registers,
+            program,
+            pc: 0,
+            cycles: 0,
+            instruction_limit: None,
+            trace: None,
+            profile: None,
+        }
+    }
+
+    /// Stops the iterator after this many instructions, for programs that may not terminate.
+    pub fn with_instruction_limit(mut self, limit: usize) -> Self {
+        self.instruction_limit = Some(limit);
+        self
+    }
+
+    /// Records every executed instruction.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    /// Counts how often each instruction is executed.
+    pub fn with_profile(mut self) -> Self {
+        self.profile = Some(vec![0; self.program.len()]);
+        self
+    }
+
+    pub fn trace(&self) -> Option<&[TraceEntry<I, N>]> {
+        self.trace.as_deref()
+    }
+
+    /// The number of times each instruction was executed, indexed like `program`.
+    pub fn profile(&self) -> Option<&[usize]> {
+        self.profile.as_deref()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    /// True if the iterator stopped because of the instruction limit, rather than halting.
+    pub fn limit_reached(&self) -> bool {
+        !self.is_halted()
+            && self
+                .instruction_limit
+                .is_some_and(|limit| self.cycles >= limit)
+    }
+
+    /// Executes the instruction at the program counter, returning its output, if any.
+    pub fn run_step(&mut self) -> Option<I::Output> {
+        let pc = self.pc;
+        let before = self.registers;
+        let instruction = &self.program[pc];
+
+        let output = match I::execute(instruction, &mut self.registers, pc) {
+            Effect::Next => {
+                self.pc += 1;
+                None
+            }
+            Effect::Jump(target) => {
+                self.pc = target;
+                None
+            }
+            Effect::Output(output) => {
+                self.pc += 1;
+                Some(output)
+            }
+            Effect::Halt => {
+                self.pc = self.program.len();
+                None
+            }
+        };
+
+        if let Some(profile) = &mut self.profile {
+            profile[pc] += 1;
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                cycle: self.cycles,
+                pc,
+                instruction: instruction.clone(),
+                before,
+                after: self.registers,
+                output: output.clone(),
+            });
+        }
+        self.cycles += 1;
+
+        output
+    }
+
+    /// Runs until the program halts or the instruction limit is reached, discarding the output.
+    pub fn run(&mut self) {
+        self.by_ref().for_each(drop);
+    }
+}
+
+impl<I: InstructionSet, const N: usize> Iterator for Machine<'_, I, N> {
+    type Item = I::Output;
+    fn next(&mut self) -> Option<I::Output> {
+        while !self.is_halted() {
+            if self.limit_reached() {
+                return None;
+            }
+            if let Some(output) = self.run_step() {
+                return Some(output);
+            }
+        }
+        None
+    }
+}
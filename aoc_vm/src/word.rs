@@ -0,0 +1,159 @@
+//! The register type of a virtual machine.
+
+use std::fmt::{Binary, Debug, Display, Octal};
+use std::ops::BitXor;
+
+use bnum::{BInt, BUint};
+
+/// A machine word: the type of a register.
+///
+/// The operations are the ones Advent of Code machines need, defined so they can't overflow or
+/// panic on large operands. Any fixed-width integer type can implement it; the primitive integers
+/// do out of the box, and so do bnum's `BUint<N>` and `BInt<N>`, for registers of any number of
+/// 64-bit digits.
+pub trait Word:
+    Copy + Eq + Ord + Default + Debug + Display + Octal + Binary + BitXor<Output = Self>
+{
+    const ZERO: Self;
+
+    fn from_u8(value: u8) -> Self;
+
+    /// `self >> amount`, where shifting by the word size or more shifts out every bit, instead of
+    /// overflowing. Panics on a negative amount.
+    fn shift_right(self, amount: Self) -> Self;
+
+    /// The lowest `bits` bits (at most 8), which is `self.rem_euclid(1 << bits)`.
+    fn low_bits(self, bits: u32) -> u8;
+}
+
+macro_rules! implement_word {
+    (signed: $($signed:ty),*; unsigned: $($unsigned:ty),*) => {
+        $(implement_word!(@word $signed, amount, {
+            assert!(amount >= 0, "negative shift amount {amount}");
+        });)*
+        $(implement_word!(@word $unsigned, amount, {});)*
+    };
+    (@word $t:ty, $amount:ident, $check:block) => {
+        impl Word for $t {
+            const ZERO: Self = 0;
+
+            fn from_u8(value: u8) -> Self {
+                value as $t
+            }
+
+            fn shift_right(self, $amount: Self) -> Self {
+                $check
+                match u32::try_from($amount) {
+                    Ok(amount) if amount < <$t>::BITS => self >> amount,
+                    // Shifting by BITS - 1 keeps the sign, which is all that's left
+                    _ => self >> (<$t>::BITS - 1) >> 1,
+                }
+            }
+
+            fn low_bits(self, bits: u32) -> u8 {
+                debug_assert!(bits <= 8);
+                // Truncating keeps the low bits, also for negative numbers
+                self as u8 & ((1u16 << bits) - 1) as u8
+            }
+        }
+    };
+}
+
+implement_word!(signed: i8, i16, i32, i64, i128, isize; unsigned: u8, u16, u32, u64, u128, usize);
+
+macro_rules! implement_big_word {
+    ($t:ident, $amount:ident, $check:block) => {
+        impl<const N: usize> Word for $t<N> {
+            const ZERO: Self = Self::ZERO;
+
+            fn from_u8(value: u8) -> Self {
+                Self::from(value)
+            }
+
+            fn shift_right(self, $amount: Self) -> Self {
+                $check
+                match u32::try_from($amount) {
+                    Ok(amount) if amount < Self::BITS => self >> amount,
+                    _ => self >> (Self::BITS - 1) >> 1,
+                }
+            }
+
+            fn low_bits(self, bits: u32) -> u8 {
+                debug_assert!(bits <= 8);
+                // Two's complement, like the primitives, so this works for negative numbers too
+                let mask = Self::from(((1u16 << bits) - 1) as u8);
+                u8::try_from(self & mask).unwrap()
+            }
+        }
+    };
+}
+
+implement_big_word!(BInt, amount, {
+    assert!(amount >= Self::ZERO, "negative shift amount {amount}");
+});
+implement_big_word!(BUint, amount, {});
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::small(729, 3, 91)]
+    #[case::word_size(729, 64, 0)]
+    #[case::huge(729, i64::MAX, 0)]
+    #[case::negative_word_size(-729, 64, -1)]
+    fn test_shift_right_i64(#[case] value: i64, #[case] amount: i64, #[case] expected: i64) {
+        assert_eq!(value.shift_right(amount), expected);
+    }
+
+    #[rstest]
+    #[case::small(1 << 100, 99, 2)]
+    #[case::word_size(u128::MAX, 128, 0)]
+    #[case::huge(u128::MAX, u128::MAX, 0)]
+    fn test_shift_right_u128(#[case] value: u128, #[case] amount: u128, #[case] expected: u128) {
+        assert_eq!(value.shift_right(amount), expected);
+    }
+
+    #[rstest]
+    #[case::small(BUint::ONE << 200, BUint::from(199u32), BUint::from(2u32))]
+    #[case::word_size(BUint::MAX, BUint::from(256u32), BUint::ZERO)]
+    #[case::huge(BUint::MAX, BUint::MAX, BUint::ZERO)]
+    fn test_shift_right_buint(
+        #[case] value: BUint<4>,
+        #[case] amount: BUint<4>,
+        #[case] expected: BUint<4>,
+    ) {
+        assert_eq!(value.shift_right(amount), expected);
+    }
+
+    #[test]
+    fn test_big_signed() {
+        let minus_729 = BInt::<4>::from(-729i32);
+        assert_eq!(minus_729.shift_right(BInt::from(256u32)), BInt::from(-1i32));
+        assert_eq!(minus_729.shift_right(BInt::from(3u32)), BInt::from(-92i32));
+        assert_eq!(BInt::<4>::from(-1i32).low_bits(3), 7);
+        assert_eq!(BInt::<4>::from(13u32).low_bits(3), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative shift amount -1")]
+    fn test_shift_right_big_negative() {
+        BInt::<4>::ONE.shift_right(BInt::from(-1i32));
+    }
+
+    #[test]
+    #[should_panic(expected = "negative shift amount -1")]
+    fn test_shift_right_negative() {
+        1i64.shift_right(-1);
+    }
+
+    #[rstest]
+    #[case(13, 3, 5)]
+    #[case(-1, 3, 7)]
+    #[case(-1, 8, 255)]
+    fn test_low_bits(#[case] value: i32, #[case] bits: u32, #[case] expected: u8) {
+        assert_eq!(value.low_bits(bits), expected);
+    }
+}
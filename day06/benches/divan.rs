@@ -32,6 +32,22 @@ fn part2(bencher: Bencher) {
     bencher.bench_local(|| puzzle_part2::solve(divan::black_box(&parsed)));
 }
 
+#[divan::bench]
+fn part2_jump_table(bencher: Bencher) {
+    let puzzle_input = include_str!("../input.txt");
+    let parsed = puzzle_input::PuzzleInput::try_from(divan::black_box(puzzle_input)).unwrap();
+
+    bencher.bench_local(|| puzzle_part2::solve_jump_table(divan::black_box(&parsed)));
+}
+
+#[divan::bench]
+fn part2_stepwise(bencher: Bencher) {
+    let puzzle_input = include_str!("../input.txt");
+    let parsed = puzzle_input::PuzzleInput::try_from(divan::black_box(puzzle_input)).unwrap();
+
+    bencher.bench_local(|| puzzle_part2::solve_stepwise(divan::black_box(&parsed)));
+}
+
 #[divan::bench]
 fn part1example(bencher: Bencher) {
     let puzzle_input = include_str!("../example_input.txt");
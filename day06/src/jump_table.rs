@@ -0,0 +1,173 @@
+use aoc_grid::{Coordinate, Direction, Grid};
+use aoc_math::detect_cycle;
+
+use crate::puzzle_input::MapElement;
+
+// The index of a cardinal direction in a cell of the jump table
+const fn index(direction: Direction) -> usize {
+    direction as usize / 2
+}
+
+/// For every cell and direction, where a guard walking that way stops: the cell just before the
+/// next obstacle, or None if the guard walks off the map. Lets the guard move from obstacle to
+/// obstacle, instead of one cell at a time.
+pub struct JumpTable {
+    stops: Grid<[Option<Coordinate>; 4]>,
+}
+
+impl JumpTable {
+    pub fn new(map: &Grid<MapElement>) -> Self {
+        let mut stops: Grid<[Option<Coordinate>; 4]> = Grid::new(map.width(), map.height());
+        let mut cells = map.iter_coordinates().collect::<Vec<_>>();
+
+        for direction in Direction::CARDINAL_4 {
+            // Fill in the cells furthest along first, so the cell in front is always done
+            let step = Coordinate::from(direction);
+            cells.sort_by_key(|c| -c.dot(step));
+
+            for &c in &cells {
+                let next = c + &direction;
+                stops[c][index(direction)] = match map.get(next) {
+                    None => None,
+                    Some(MapElement::Obstacle) => Some(c),
+                    Some(_) => stops[next][index(direction)],
+                };
+            }
+        }
+        JumpTable { stops }
+    }
+
+    /// Where a guard at `position` walking in `direction` stops, with an obstacle added at
+    /// `extra_obstacle`. None if the guard walks off the map.
+    pub fn next_stop(
+        &self,
+        position: Coordinate,
+        direction: Direction,
+        extra_obstacle: Coordinate,
+    ) -> Option<Coordinate> {
+        let stop = self.stops[position][index(direction)];
+
+        // The extra obstacle only matters if it's in the way, before the stop
+        let step = Coordinate::from(direction);
+        let offset = extra_obstacle - position;
+        let distance = offset.dot(step);
+        let in_line = offset.cross(step) == 0 && distance > 0;
+        if in_line && stop.is_none_or(|stop| distance <= (stop - position).dot(step)) {
+            Some(extra_obstacle - step)
+        } else {
+            stop
+        }
+    }
+
+    /// The guard's turns, starting at `start` facing `direction`, with an obstacle added at
+    /// `extra_obstacle`: where the guard turns, and the direction they walk on in. Ends when the
+    /// guard walks off the map, which never happens if they're in a loop.
+    pub fn turns(
+        &self,
+        start: Coordinate,
+        direction: Direction,
+        extra_obstacle: Coordinate,
+    ) -> impl Iterator<Item = (Coordinate, Direction)> + '_ {
+        let mut guard = Some((start, direction));
+        std::iter::from_fn(move || {
+            let (position, direction) = guard?;
+            guard = self
+                .next_stop(position, direction, extra_obstacle)
+                .map(|stop| (stop, direction.clockwise_4()));
+            guard
+        })
+    }
+
+    /// Whether a guard starting at `start`, facing `direction`, ends up walking in a loop once an
+    /// obstacle is added at `extra_obstacle`.
+    pub fn does_loop(
+        &self,
+        start: Coordinate,
+        direction: Direction,
+        extra_obstacle: Coordinate,
+    ) -> bool {
+        // The guard is in a loop when they turn at the same position, in the same direction, again
+        detect_cycle(self.turns(start, direction, extra_obstacle)).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::puzzle_input::PuzzleInput;
+
+    const EXAMPLE_INPUT: &str = include_str!("../example_input.txt");
+
+    // Far outside the map, so it's never in the way
+    const NO_OBSTACLE: Coordinate = Coordinate(-2, -2);
+
+    #[rstest]
+    #[case::up(Coordinate(4, 6), Direction::Up, NO_OBSTACLE, Some(Coordinate(4, 1)))]
+    #[case::right(
+        Coordinate(4, 1),
+        Direction::Right,
+        NO_OBSTACLE,
+        Some(Coordinate(8, 1))
+    )]
+    #[case::against_obstacle(Coordinate(4, 1), Direction::Up, NO_OBSTACLE, Some(Coordinate(4, 1)))]
+    #[case::off_the_map(Coordinate(1, 7), Direction::Down, NO_OBSTACLE, None)]
+    #[case::extra_obstacle(
+        Coordinate(4, 6),
+        Direction::Up,
+        Coordinate(4, 3),
+        Some(Coordinate(4, 4))
+    )]
+    #[case::extra_obstacle_next_to_guard(
+        Coordinate(4, 6),
+        Direction::Up,
+        Coordinate(4, 5),
+        Some(Coordinate(4, 6))
+    )]
+    #[case::extra_obstacle_behind(
+        Coordinate(4, 6),
+        Direction::Up,
+        Coordinate(4, 7),
+        Some(Coordinate(4, 1))
+    )]
+    #[case::extra_obstacle_past_obstacle(
+        Coordinate(4, 1),
+        Direction::Right,
+        Coordinate(9, 1),
+        Some(Coordinate(8, 1))
+    )]
+    #[case::extra_obstacle_off_the_map_path(
+        Coordinate(1, 7),
+        Direction::Down,
+        Coordinate(1, 9),
+        Some(Coordinate(1, 8))
+    )]
+    fn test_next_stop(
+        #[case] position: Coordinate,
+        #[case] direction: Direction,
+        #[case] extra_obstacle: Coordinate,
+        #[case] expected: Option<Coordinate>,
+    ) {
+        let input = PuzzleInput::try_from(EXAMPLE_INPUT).unwrap();
+        let table = JumpTable::new(&input.map);
+        assert_eq!(
+            table.next_stop(position, direction, extra_obstacle),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::no_obstacle(NO_OBSTACLE, false)]
+    #[case::first_loop(Coordinate(3, 6), true)]
+    #[case::last_loop(Coordinate(7, 9), true)]
+    #[case::no_loop(Coordinate(2, 1), false)]
+    fn test_does_loop(#[case] extra_obstacle: Coordinate, #[case] expected: bool) {
+        let input = PuzzleInput::try_from(EXAMPLE_INPUT).unwrap();
+        let table = JumpTable::new(&input.map);
+        assert_eq!(
+            table.does_loop(Coordinate(4, 6), Direction::Up, extra_obstacle),
+            expected
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod jump_table;
+pub mod loops;
 pub mod puzzle_input;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use aoc_grid::{Coordinate, Direction, Grid};
+use aoc_math::detect_cycle;
+
+use crate::jump_table::JumpTable;
+use crate::puzzle_input::{MapElement, PuzzleInput};
+use crate::puzzle_part2::candidate_obstacles;
+
+/// A loop that an added obstacle traps the guard in.
+#[derive(Debug, PartialEq, Clone)]
+pub struct GuardLoop {
+    pub obstacle: Coordinate,
+    /// The guard that loops, as an index in `PuzzleInput::guards`.
+    pub guard: usize,
+    /// The turns that make up the loop, in order: where the guard turns, and the direction they
+    /// walk on in.
+    pub cycle: Vec<(Coordinate, Direction)>,
+    /// The number of steps the guard takes until they're back at a turn they took before: the step
+    /// at which the loop closes.
+    pub closes_at: usize,
+}
+
+impl Display for GuardLoop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "obstacle at {:?} traps guard {}: loop of {} turns, closes at step {}",
+            self.obstacle,
+            self.guard,
+            self.cycle.len(),
+            self.closes_at
+        )
+    }
+}
+
+/// The loop that guard `guard` ends up in when an obstacle is added at `obstacle`, if any.
+pub fn find_loop(
+    table: &JumpTable,
+    guards: &[(Coordinate, Direction)],
+    guard: usize,
+    obstacle: Coordinate,
+) -> Option<GuardLoop> {
+    let (start, direction) = guards[guard];
+
+    // Remember the turns, including the repeated one that closes the loop
+    let mut turns = vec![];
+    let cycle = detect_cycle(
+        table
+            .turns(start, direction, obstacle)
+            .inspect(|&turn| turns.push(turn)),
+    )?;
+
+    let closes_at = std::iter::once(start)
+        .chain(turns.iter().map(|&(position, _)| position))
+        .collect::<Vec<_>>()
+        .windows(2)
+        .map(|w| w[0].manhattan_distance(w[1]))
+        .sum();
+    Some(GuardLoop {
+        obstacle,
+        guard,
+        cycle: turns[cycle.start..cycle.start + cycle.length].to_vec(),
+        closes_at,
+    })
+}
+
+/// Every obstacle position that traps a guard in a loop, in reading order, with the loop. An
+/// obstacle that traps several guards is listed once for each.
+pub fn find_loops(input: &PuzzleInput) -> Vec<GuardLoop> {
+    let guards = input.guards();
+    let table = JumpTable::new(&input.map);
+
+    let mut candidates = candidate_obstacles(input, &guards)
+        .into_iter()
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|c| (c.1, c.0));
+    candidates
+        .into_iter()
+        .flat_map(|obstacle| {
+            let (table, guards) = (&table, &guards);
+            (0..guards.len()).filter_map(move |guard| find_loop(table, guards, guard, obstacle))
+        })
+        .collect()
+}
+
+/// Draws the guards' routes on the map: `|` where they walked up or down, `-` where they walked left
+/// or right, and `+` where they did both or turned. `O` is the added obstacle, if any. A route ends
+/// when the guard walks off the map, or when their loop closes.
+pub fn render(input: &PuzzleInput, obstacle: Option<Coordinate>) -> String {
+    let guards = input.guards();
+    let table = JumpTable::new(&input.map);
+    let extra_obstacle = obstacle.unwrap_or(Coordinate::new(-2, -2));
+
+    // Whether each cell was walked vertically and horizontally
+    let mut walked: Grid<(bool, bool)> = Grid::new(input.map.width(), input.map.height());
+    for &(start, start_direction) in &guards {
+        walk(
+            input,
+            &table,
+            start,
+            start_direction,
+            extra_obstacle,
+            &mut walked,
+        );
+    }
+
+    let mut map = input
+        .map
+        .zip(&walked, |(&element, &walked)| match (element, walked) {
+            // Guards are drawn where they start
+            (MapElement::Guard(_), _) => char::from(element),
+            (_, (true, true)) => '+',
+            (_, (true, false)) => '|',
+            (_, (false, true)) => '-',
+            (_, (false, false)) => char::from(element),
+        });
+    if let Some(obstacle) = obstacle {
+        map[obstacle] = 'O';
+    }
+    map.to_string()
+}
+
+// Marks the cells a guard walks through, until they walk off the map or their loop closes
+fn walk(
+    input: &PuzzleInput,
+    table: &JumpTable,
+    start: Coordinate,
+    start_direction: Direction,
+    extra_obstacle: Coordinate,
+    walked: &mut Grid<(bool, bool)>,
+) {
+    let mut turns = HashSet::new();
+    let (mut position, mut direction) = (start, start_direction);
+    loop {
+        let stop = table.next_stop(position, direction, extra_obstacle);
+        let mut c = position;
+        while input.map.contains(c) {
+            match direction {
+                Direction::Up | Direction::Down => walked[c].0 = true,
+                _ => walked[c].1 = true,
+            }
+            if Some(c) == stop {
+                break;
+            }
+            c += &direction;
+        }
+
+        match stop {
+            Some(stop) if turns.insert((stop, direction)) => {
+                position = stop;
+                direction = direction.clockwise_4();
+            }
+            _ => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = include_str!("../example_input.txt");
+
+    #[test]
+    fn test_find_loops() {
+        let input = PuzzleInput::try_from(EXAMPLE_INPUT).unwrap();
+        let loops = find_loops(&input);
+        assert_eq!(
+            loops.iter().map(|l| l.obstacle).collect::<Vec<_>>(),
+            vec![
+                Coordinate(3, 6),
+                Coordinate(6, 7),
+                Coordinate(7, 7),
+                Coordinate(1, 8),
+                Coordinate(3, 8),
+                Coordinate(7, 9)
+            ]
+        );
+
+        assert_eq!(
+            loops[0].cycle,
+            vec![
+                (Coordinate(4, 1), Direction::Right),
+                (Coordinate(8, 1), Direction::Down),
+                (Coordinate(8, 6), Direction::Left),
+                (Coordinate(4, 6), Direction::Up),
+            ]
+        );
+        assert_eq!(
+            loops[0].to_string(),
+            "obstacle at (3, 6) traps guard 0: loop of 4 turns, closes at step 23"
+        );
+    }
+
+    #[test]
+    fn test_multiple_guards() {
+        let input =
+            PuzzleInput::try_from(".#...\n....#\n.^...\n...#.\n.#...\n...v.\n#....\n...#.\n")
+                .unwrap();
+        let loops = find_loops(&input);
+        assert_eq!(
+            loops
+                .iter()
+                .map(|l| (l.obstacle, l.guard))
+                .collect::<Vec<_>>(),
+            vec![(Coordinate(0, 2), 0), (Coordinate(4, 5), 1)]
+        );
+        assert_eq!(
+            render(&input, Some(Coordinate(4, 5))),
+            concat!(
+                ".#...\n", ".+-+#\n", "-^-+.\n", "...#.\n", ".#...\n", ".+-vO\n", "#+-+.\n",
+                "...#.\n",
+            )
+        );
+    }
+
+    #[rstest]
+    #[case::no_obstacle(
+        None,
+        concat!(
+            "....#.....\n",
+            "....+---+#\n",
+            "....|...|.\n",
+            "..#.|...|.\n",
+            "..+-+-+#|.\n",
+            "..|.|.|.|.\n",
+            ".#+-^-+-+.\n",
+            ".+----++#.\n",
+            "#+----+|..\n",
+            "......#|..\n",
+        )
+    )]
+    #[case::first_loop(
+        Some(Coordinate(3, 6)),
+        concat!(
+            "....#.....\n",
+            "....+---+#\n",
+            "....|...|.\n",
+            "..#.|...|.\n",
+            "....|..#|.\n",
+            "....|...|.\n",
+            ".#.O^---+.\n",
+            "........#.\n",
+            "#.........\n",
+            "......#...\n",
+        )
+    )]
+    #[case::second_loop(
+        Some(Coordinate(6, 7)),
+        concat!(
+            "....#.....\n",
+            "....+---+#\n",
+            "....|...|.\n",
+            "..#.|...|.\n",
+            "..+-+-+#|.\n",
+            "..|.|.|.|.\n",
+            ".#+-^-+-+.\n",
+            "......O.#.\n",
+            "#.........\n",
+            "......#...\n",
+        )
+    )]
+    // The guard walks past this cell later on, but with the obstacle there from the start, they
+    // turn right away and leave the map
+    #[case::walked_cell(
+        Some(Coordinate(4, 5)),
+        concat!(
+            "....#.....\n",
+            ".........#\n",
+            "..........\n",
+            "..#.......\n",
+            ".......#..\n",
+            "....O.....\n",
+            ".#..^-----\n",
+            "........#.\n",
+            "#.........\n",
+            "......#...\n",
+        )
+    )]
+    fn test_render(#[case] obstacle: Option<Coordinate>, #[case] expected: &str) {
+        let input = PuzzleInput::try_from(EXAMPLE_INPUT).unwrap();
+        assert_eq!(render(&input, obstacle), expected);
+    }
+}
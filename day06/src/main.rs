@@ -1,3 +1,5 @@
+pub mod jump_table;
+pub mod loops;
 pub mod puzzle_input;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
@@ -10,6 +12,13 @@ fn main() {
         Err(e) => {
             println!("Error: {e:?}");
         }
+        Ok(input) if std::env::args().nth(1).as_deref() == Some("loops") => {
+            // Show every loop, with the route drawn on the map
+            for guard_loop in loops::find_loops(&input) {
+                println!("{guard_loop}");
+                println!("{}", loops::render(&input, Some(guard_loop.obstacle)));
+            }
+        }
         Ok(input) => {
             println!("day06 - part 1: {}", puzzle_part1::solve(&input));
             println!("day06 - part 2: {}", puzzle_part2::solve(&input));
@@ -1,4 +1,4 @@
-use aoc_grid::Grid;
+use aoc_grid::{Coordinate, Direction, Grid};
 use nom::{
     character::complete::{line_ending, multispace0, one_of},
     combinator::map,
@@ -12,7 +12,8 @@ use nom::{
 pub enum MapElement {
     Empty,
     Obstacle,
-    Guard,
+    /// A guard's start position, and the direction they face.
+    Guard(Direction),
 }
 
 impl From<char> for MapElement {
@@ -20,7 +21,10 @@ impl From<char> for MapElement {
         match c {
             '.' => MapElement::Empty,
             '#' => MapElement::Obstacle,
-            '^' => MapElement::Guard,
+            '^' => MapElement::Guard(Direction::Up),
+            '>' => MapElement::Guard(Direction::Right),
+            'v' => MapElement::Guard(Direction::Down),
+            '<' => MapElement::Guard(Direction::Left),
             _ => panic!("Invalid grid element: {}", c),
         }
     }
@@ -31,7 +35,11 @@ impl From<MapElement> for char {
         match e {
             MapElement::Empty => '.',
             MapElement::Obstacle => '#',
-            MapElement::Guard => '^',
+            MapElement::Guard(Direction::Up) => '^',
+            MapElement::Guard(Direction::Right) => '>',
+            MapElement::Guard(Direction::Down) => 'v',
+            MapElement::Guard(Direction::Left) => '<',
+            MapElement::Guard(direction) => panic!("Guards can't face {direction:?}"),
         }
     }
 }
@@ -42,10 +50,23 @@ pub struct PuzzleInput {
     pub map: Grid<MapElement>,
 }
 
+impl PuzzleInput {
+    /// The guards' start positions and directions, in reading order.
+    pub fn guards(&self) -> Vec<(Coordinate, Direction)> {
+        self.map
+            .iter_pairs()
+            .filter_map(|(c, e)| match *e {
+                MapElement::Guard(direction) => Some((c, direction)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 fn parse_puzzle(input: &str) -> IResult<&str, PuzzleInput> {
     // Main parser for the puzzle
     let puzzle_parser = map(
-        separated_list1(line_ending, many1(map(one_of("#.^"), MapElement::from))),
+        separated_list1(line_ending, many1(map(one_of("#.^>v<"), MapElement::from))),
         |value| PuzzleInput {
             map: Grid::from(value),
         },
@@ -92,12 +113,29 @@ mod tests {
             input
                 .map
                 .iter()
-                .filter(|&e| *e == MapElement::Guard)
+                .filter(|&e| matches!(e, MapElement::Guard(_)))
                 .count()
                 == 1
         );
     }
 
+    #[test]
+    fn test_guards() {
+        let input = PuzzleInput::try_from("..>.\n#...\nv..<\n").unwrap();
+        assert_eq!(
+            input.guards(),
+            vec![
+                (Coordinate(2, 0), Direction::Right),
+                (Coordinate(0, 2), Direction::Down),
+                (Coordinate(3, 2), Direction::Left),
+            ]
+        );
+        assert_eq!(
+            input.map.map(|&e| char::from(e)).to_string(),
+            "..>.\n#...\nv..<\n"
+        );
+    }
+
     #[rstest]
     #[case::example_input("Not valid input")]
     #[case::final_input("")]
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use aoc_grid::{Coordinate, Direction, Grid};
 
 use crate::puzzle_input::{MapElement, PuzzleInput};
@@ -9,60 +11,134 @@ enum Visited {
     Visited,
 }
 
+/// Two guards in the same place at the same time.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Collision {
+    /// The number of steps the guards had taken.
+    pub step: usize,
+    /// Where they met. When two guards walk through each other, this is where the first one ended
+    /// up.
+    pub position: Coordinate,
+    /// The guards that collided, as indexes in `PuzzleInput::guards`.
+    pub guards: (usize, usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GuardState {
+    position: Coordinate,
+    direction: Direction,
+    on_map: bool,
+    // Back in a position and direction they've been in before, so they'll walk the same loop
+    // forever
+    looping: bool,
+}
+
+impl GuardState {
+    fn is_walking(&self) -> bool {
+        self.on_map && !self.looping
+    }
+}
+
+// The guards walk at the same time, one step each, without getting in each other's way. A guard
+// stops when they walk off the map, or into a loop: from then on, they don't visit anything new.
 struct MapState {
     map: Grid<MapElement>,
     visited: Grid<Visited>,
-    guard_position: Coordinate,
-    guard_direction: Direction,
+    guards: Vec<GuardState>,
+    // The positions and directions each guard has been in, by guard index
+    seen: HashSet<(usize, Coordinate, Direction)>,
+    steps: usize,
+    collisions: Vec<Collision>,
 }
 
 impl MapState {
-    fn new(base_map: &Grid<MapElement>) -> Self {
+    fn new(input: &PuzzleInput) -> Self {
+        let base_map = &input.map;
         let mut result = Self {
-            // Remove the guard from the map
+            // Remove the guards from the map
             map: base_map.map(|&c| match c {
-                MapElement::Guard => MapElement::Empty,
+                MapElement::Guard(_) => MapElement::Empty,
                 _ => c,
             }),
             // Mark no position as visited yet
             visited: Grid::new(base_map.width(), base_map.height()),
 
-            // Look up the guard position
-            guard_position: base_map
-                .iter_coordinates()
-                .find(|&c| base_map[c] == MapElement::Guard)
-                .expect("map should have a guard position"),
-
-            // Guard always starts facing up
-            guard_direction: Direction::Up,
+            guards: input
+                .guards()
+                .into_iter()
+                .map(|(position, direction)| GuardState {
+                    position,
+                    direction,
+                    on_map: true,
+                    looping: false,
+                })
+                .collect(),
+            seen: HashSet::new(),
+            steps: 0,
+            collisions: vec![],
         };
 
-        result.visited[result.guard_position] = Visited::Visited;
+        for (index, guard) in result.guards.iter().enumerate() {
+            result.visited[guard.position] = Visited::Visited;
+            result.seen.insert((index, guard.position, guard.direction));
+        }
         result
     }
 
-    fn step(&mut self) -> bool {
-        let mut next_position = self.guard_position + &self.guard_direction;
+    // Moves a guard one step, returning whether they're still on the map
+    fn step_guard(&mut self, index: usize) -> bool {
+        let guard = &mut self.guards[index];
+        let mut next_position = guard.position + &guard.direction;
         let mut next_element = self.map.get(next_position);
 
         while next_element == Some(&MapElement::Obstacle) {
-            self.guard_direction = self.guard_direction.clockwise_4();
-            next_position = self.guard_position + &self.guard_direction;
+            guard.direction = guard.direction.clockwise_4();
+            next_position = guard.position + &guard.direction;
             next_element = self.map.get(next_position);
         }
 
-        if next_element.is_some() {
-            self.guard_position = next_position;
-            self.visited[self.guard_position] = Visited::Visited;
-            true
-        } else {
-            false
+        guard.position = next_position;
+        guard.on_map = next_element.is_some();
+        if guard.on_map {
+            self.visited[next_position] = Visited::Visited;
+            guard.looping = !self.seen.insert((index, next_position, guard.direction));
+        }
+        guard.on_map
+    }
+
+    fn step(&mut self) -> bool {
+        let before = self.guards.clone();
+        for index in 0..self.guards.len() {
+            if self.guards[index].is_walking() {
+                self.step_guard(index);
+            }
         }
+        self.steps += 1;
+
+        // Guards collide when they end up in the same place, or walk through each other. Guards
+        // that stopped walking in a loop before this step aren't followed any more.
+        for (a, guard_a) in self.guards.iter().enumerate() {
+            for (b, guard_b) in self.guards.iter().enumerate().skip(a + 1) {
+                let met = guard_a.position == guard_b.position
+                    || (guard_a.position == before[b].position
+                        && guard_b.position == before[a].position);
+                let walked = before[a].is_walking() && before[b].is_walking();
+                if walked && guard_a.on_map && guard_b.on_map && met {
+                    self.collisions.push(Collision {
+                        step: self.steps,
+                        position: guard_a.position,
+                        guards: (a, b),
+                    });
+                }
+            }
+        }
+
+        self.guards.iter().any(GuardState::is_walking)
     }
 }
 
 pub fn solve(input: &PuzzleInput) -> String {
-    let mut state = MapState::new(&input.map);
+    let mut state = MapState::new(input);
 
     while state.step() {}
 
@@ -74,6 +150,15 @@ pub fn solve(input: &PuzzleInput) -> String {
         .to_string()
 }
 
+/// Every collision between guards, until every guard has walked off the map or into a loop.
+pub fn collisions(input: &PuzzleInput) -> Vec<Collision> {
+    let mut state = MapState::new(input);
+
+    while state.step() {}
+
+    state.collisions
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -88,4 +173,31 @@ mod tests {
         let input = PuzzleInput::try_from(input).unwrap();
         assert_eq!(solve(&input), expected);
     }
+
+    #[rstest]
+    #[case::single_guard("..^..\n", 1)]
+    #[case::two_guards("#....\n.^...\n>....\n", 7)]
+    #[case::same_path("..v..\n.....\n..^..\n", 3)]
+    // The guard walks around the four cells in the middle forever
+    #[case::looping(".#..\n.^.#\n#...\n..#.\n", 4)]
+    #[case::looping_and_leaving(".#..\n.^.#\n#...\n..#.\n>...\n", 8)]
+    fn test_solve_multiple_guards(#[case] input: &str, #[case] expected: usize) {
+        let input = PuzzleInput::try_from(input).unwrap();
+        assert_eq!(solve(&input), expected.to_string());
+    }
+
+    #[rstest]
+    #[case::none("#....\n.^...\n>....\n", vec![])]
+    #[case::meet(">.<\n", vec![Collision { step: 1, position: Coordinate(1, 0), guards: (0, 1) }])]
+    #[case::walk_through(">..<\n", vec![Collision { step: 2, position: Coordinate(2, 0), guards: (0, 1) }])]
+    // The first guard turns at the obstacle, into the second one's path
+    #[case::after_turn(
+        "...v\n...#\n.^..\n",
+        vec![Collision { step: 2, position: Coordinate(1, 0), guards: (0, 1) }]
+    )]
+    #[case::looping(".#..\n.^.#\n#...\n..#.\n", vec![])]
+    fn test_collisions(#[case] input: &str, #[case] expected: Vec<Collision>) {
+        let input = PuzzleInput::try_from(input).unwrap();
+        assert_eq!(collisions(&input), expected);
+    }
 }
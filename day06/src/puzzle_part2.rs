@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 
 use aoc_grid::{Coordinate, Direction, Grid};
+use aoc_math::detect_cycle;
+use rayon::prelude::*;
 
+use crate::jump_table::JumpTable;
 use crate::puzzle_input::{MapElement, PuzzleInput};
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
@@ -35,45 +38,82 @@ impl Guard {
 }
 
 fn does_loop(map: &Grid<MapElement>, mut guard: Guard, extra_obstacle: &Coordinate) -> bool {
-    let mut visited = Grid::<Option<Direction>>::new(map.width(), map.height());
-    loop {
-        if !guard.step(map, extra_obstacle) {
-            // Guard walked off the map
-            return false;
-        }
-        if visited[guard.position] == Some(guard.direction) {
-            return true;
-        }
-
-        visited[guard.position] = Some(guard.direction);
-    }
+    // The guard is in a loop when they're in the same position, facing the same direction. A guard
+    // that walks off the map ends the sequence.
+    let states = std::iter::from_fn(|| guard.step(map, extra_obstacle).then_some(guard));
+    detect_cycle(states).is_some()
 }
 
-pub fn solve(input: &PuzzleInput) -> String {
-    let guard_position = input
-        .map
-        .iter_coordinates()
-        .find(|&c| input.map[c] == MapElement::Guard)
-        .expect("map should have a guard position");
-
-    // Build a list of locations that the guard has visited in an unaltered map
-    let mut guard = Guard::new(guard_position, Direction::Up);
+// The locations that the guards visit in an unaltered map, except where they start. Obstacles
+// anywhere else don't change the guards' paths. A guard that walks in a loop on the unaltered map
+// has visited their whole path once they're back in a position and direction they've been in.
+pub(crate) fn candidate_obstacles(
+    input: &PuzzleInput,
+    guards: &[(Coordinate, Direction)],
+) -> HashSet<Coordinate> {
     let mut visited_positions = HashSet::with_capacity(input.map.width() * 10);
     let fake_obstacle = Coordinate::new(-2, -2);
-    while guard.step(&input.map, &fake_obstacle) {
-        if guard.position != guard_position {
-            visited_positions.insert(guard.position);
-        }
+    for &(position, direction) in guards {
+        let mut guard = Guard::new(position, direction);
+        let path = std::iter::once(guard)
+            .chain(std::iter::from_fn(|| {
+                guard.step(&input.map, &fake_obstacle).then_some(guard)
+            }))
+            .inspect(|guard| {
+                visited_positions.insert(guard.position);
+            });
+        detect_cycle(path);
+    }
+    for (position, _) in guards {
+        visited_positions.remove(position);
     }
-
     visited_positions
+}
+
+/// Walks the guards one cell at a time for every candidate obstacle. An obstacle counts if it
+/// traps any of the guards in a loop.
+pub fn solve_stepwise(input: &PuzzleInput) -> String {
+    let guards = input.guards();
+
+    candidate_obstacles(input, &guards)
         .iter()
         .filter(|coordinate| {
-            does_loop(
-                &input.map,
-                Guard::new(guard_position, Direction::Up),
-                coordinate,
-            )
+            guards.iter().any(|&(position, direction)| {
+                does_loop(&input.map, Guard::new(position, direction), coordinate)
+            })
+        })
+        .count()
+        .to_string()
+}
+
+/// Moves the guards from obstacle to obstacle with a jump table, one candidate obstacle at a
+/// time.
+pub fn solve_jump_table(input: &PuzzleInput) -> String {
+    let guards = input.guards();
+    let table = JumpTable::new(&input.map);
+
+    candidate_obstacles(input, &guards)
+        .into_iter()
+        .filter(|&obstacle| {
+            guards
+                .iter()
+                .any(|&(position, direction)| table.does_loop(position, direction, obstacle))
+        })
+        .count()
+        .to_string()
+}
+
+/// Like `solve_jump_table`, but tries the candidate obstacles in parallel.
+pub fn solve(input: &PuzzleInput) -> String {
+    let guards = input.guards();
+    let table = JumpTable::new(&input.map);
+
+    candidate_obstacles(input, &guards)
+        .into_par_iter()
+        .filter(|&obstacle| {
+            guards
+                .iter()
+                .any(|&(position, direction)| table.does_loop(position, direction, obstacle))
         })
         .count()
         .to_string()
@@ -91,5 +131,26 @@ mod tests {
     fn test_solve(#[case] input: &str, #[case] expected: &str) {
         let input = PuzzleInput::try_from(input).unwrap();
         assert_eq!(solve(&input), expected);
+        assert_eq!(solve_jump_table(&input), expected);
+        assert_eq!(solve_stepwise(&input), expected);
+    }
+
+    // A guard facing up, who loops if an obstacle is added to the left of where they start
+    const FACING_UP: &str = ".#...\n....#\n.^...\n...#.\n";
+    // The same, turned around
+    const FACING_DOWN: &str = ".#...\n...v.\n#....\n...#.\n";
+
+    #[rstest]
+    #[case::facing_up(FACING_UP.to_string(), "1")]
+    #[case::facing_down(FACING_DOWN.to_string(), "1")]
+    #[case::two_guards(FACING_UP.to_string() + FACING_DOWN, "2")]
+    // Already loops without an extra obstacle, but an obstacle anywhere on the loop lets the
+    // guard out
+    #[case::looping(".#..\n.^.#\n#...\n..#.\n".to_string(), "0")]
+    fn test_solve_guards(#[case] input: String, #[case] expected: &str) {
+        let input = PuzzleInput::try_from(input.as_str()).unwrap();
+        assert_eq!(solve(&input), expected);
+        assert_eq!(solve_jump_table(&input), expected);
+        assert_eq!(solve_stepwise(&input), expected);
     }
 }
@@ -4,6 +4,23 @@ use criterion::BenchmarkId;
 use criterion::Criterion;
 use criterion::{criterion_group, criterion_main};
 
+// A disk map with `files` files and spaces of pseudo-random sizes up to `max_size`, as
+// whitespace-separated integers, to see how the solvers scale past the puzzle input
+fn synthetic_disk_map(files: usize, max_size: u64) -> String {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut size = move |min: u64| {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        min + (state >> 33) % (max_size + 1 - min)
+    };
+    (0..files)
+        .flat_map(|_| [size(1), size(0)])
+        .map(|size| size.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 pub fn criterion_benchmark(c: &mut Criterion) {
     let inputs = [
         ("example", include_str!("../example_input.txt")),
@@ -22,6 +39,13 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             group.bench_with_input(BenchmarkId::new("part2", name), &parsed, |b, parsed| {
                 b.iter(|| puzzle_part2::solve(parsed));
             });
+            group.bench_with_input(
+                BenchmarkId::new("part2_used_map", name),
+                &parsed,
+                |b, parsed| {
+                    b.iter(|| puzzle_part2::solve_used_map(parsed));
+                },
+            );
             group.bench_with_input(BenchmarkId::new("complete", name), input, |b, parsed| {
                 b.iter(|| {
                     let parsed = puzzle_input::PuzzleInput::try_from(parsed).unwrap();
@@ -31,6 +55,24 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             });
         }
     }
+    group.finish();
+
+    // The used map is quadratic in the number of files, so keep the sample count down
+    let mut group = c.benchmark_group("day09_synthetic");
+    group.sample_size(10);
+    let parsed =
+        puzzle_input::PuzzleInput::try_from(synthetic_disk_map(40_000, 9).as_str()).unwrap();
+    group.bench_function("part2", |b| b.iter(|| puzzle_part2::solve(&parsed)));
+    group.bench_function("part2_used_map", |b| {
+        b.iter(|| puzzle_part2::solve_used_map(&parsed))
+    });
+
+    // Far too large for the used map
+    let parsed =
+        puzzle_input::PuzzleInput::try_from(synthetic_disk_map(1_000_000, 1_000_000).as_str())
+            .unwrap();
+    group.bench_function("part1_large", |b| b.iter(|| puzzle_part1::solve(&parsed)));
+    group.bench_function("part2_large", |b| b.iter(|| puzzle_part2::solve(&parsed)));
 }
 
 criterion_group!(benches, criterion_benchmark);
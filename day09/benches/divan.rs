@@ -5,6 +5,23 @@ fn main() {
     divan::main();
 }
 
+// A disk map with `files` files and spaces of pseudo-random sizes up to `max_size`, as
+// whitespace-separated integers, to see how the solvers scale past the puzzle input
+fn synthetic_disk_map(files: usize, max_size: u64) -> String {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut size = move |min: u64| {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        min + (state >> 33) % (max_size + 1 - min)
+    };
+    (0..files)
+        .flat_map(|_| [size(1), size(0)])
+        .map(|size| size.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[divan::bench]
 fn parsing() {
     let puzzle_input = include_str!("../input.txt");
@@ -32,6 +49,47 @@ fn part2(bencher: Bencher) {
     bencher.bench_local(|| puzzle_part2::solve(divan::black_box(&parsed)));
 }
 
+#[divan::bench]
+fn part2_used_map(bencher: Bencher) {
+    let puzzle_input = include_str!("../input.txt");
+    let parsed = puzzle_input::PuzzleInput::try_from(divan::black_box(puzzle_input)).unwrap();
+
+    bencher.bench_local(|| puzzle_part2::solve_used_map(divan::black_box(&parsed)));
+}
+
+#[divan::bench(sample_count = 10)]
+fn part2_synthetic(bencher: Bencher) {
+    let puzzle_input = synthetic_disk_map(40_000, 9);
+    let parsed = puzzle_input::PuzzleInput::try_from(puzzle_input.as_str()).unwrap();
+
+    bencher.bench_local(|| puzzle_part2::solve(divan::black_box(&parsed)));
+}
+
+#[divan::bench(sample_count = 10)]
+fn part2_used_map_synthetic(bencher: Bencher) {
+    let puzzle_input = synthetic_disk_map(40_000, 9);
+    let parsed = puzzle_input::PuzzleInput::try_from(puzzle_input.as_str()).unwrap();
+
+    bencher.bench_local(|| puzzle_part2::solve_used_map(divan::black_box(&parsed)));
+}
+
+#[divan::bench(sample_count = 10)]
+fn part1_synthetic_large(bencher: Bencher) {
+    let puzzle_input = synthetic_disk_map(1_000_000, 1_000_000);
+    let parsed = puzzle_input::PuzzleInput::try_from(puzzle_input.as_str()).unwrap();
+
+    bencher.bench_local(|| puzzle_part1::solve(divan::black_box(&parsed)));
+}
+
+// Far too large for the used map
+#[divan::bench(sample_count = 10)]
+fn part2_synthetic_large(bencher: Bencher) {
+    let puzzle_input = synthetic_disk_map(1_000_000, 1_000_000);
+    let parsed = puzzle_input::PuzzleInput::try_from(puzzle_input.as_str()).unwrap();
+
+    bencher.bench_local(|| puzzle_part2::solve(divan::black_box(&parsed)));
+}
+
 #[divan::bench]
 fn part1example(bencher: Bencher) {
     let puzzle_input = include_str!("../example_input.txt");
@@ -0,0 +1,279 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+use crate::puzzle_input::PuzzleInput;
+
+/// The disk, block by block.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Disk {
+    /// The id of the file in each block, or None for a free block.
+    pub blocks: Vec<Option<u64>>,
+}
+
+impl From<&PuzzleInput> for Disk {
+    fn from(input: &PuzzleInput) -> Self {
+        let blocks = input
+            .files
+            .iter()
+            .flat_map(|file| {
+                std::iter::repeat_n(Some(file.index), file.size as usize)
+                    .chain(std::iter::repeat_n(None, file.space_after as usize))
+            })
+            .collect();
+        Disk { blocks }
+    }
+}
+
+/// Draws the disk like the puzzle text does: a digit for the file id in each block, and `.` for a
+/// free block. File ids past 9 only show their last digit, to keep one character per block.
+impl Display for Disk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for block in &self.blocks {
+            let c = match block {
+                Some(id) => char::from_digit((id % 10) as u32, 10).unwrap(),
+                None => '.',
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Disk {
+    pub fn checksum(&self) -> u128 {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(position, block)| block.map(|id| position as u128 * id as u128))
+            .sum()
+    }
+
+    /// The blocks each file takes up, by file id. Only meaningful while files are contiguous, so
+    /// not while compacting block by block.
+    pub fn files(&self) -> Vec<Range<usize>> {
+        let mut files: Vec<Option<Range<usize>>> = vec![];
+        for (position, block) in self.blocks.iter().enumerate() {
+            if let Some(id) = *block {
+                let id = id as usize;
+                if files.len() <= id {
+                    files.resize(id + 1, None);
+                }
+                files[id].get_or_insert(position..position).end = position + 1;
+            }
+        }
+        files.into_iter().map(Option::unwrap_or_default).collect()
+    }
+
+    /// Starts compacting the disk, one move at a time.
+    pub fn compaction(self, strategy: Strategy) -> Compaction {
+        let next_file = self.files().len().checked_sub(1).map(|id| id as u64);
+        let last_used = self.blocks.len();
+        Compaction {
+            disk: self,
+            strategy,
+            first_free: 0,
+            last_used,
+            next_file,
+        }
+    }
+
+    /// The disk after compacting it completely.
+    pub fn compact(self, strategy: Strategy) -> Disk {
+        let mut compaction = self.compaction(strategy);
+        while compaction.step() {}
+        compaction.disk
+    }
+}
+
+/// How to compact a disk.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Strategy {
+    /// Part 1: move the last file block to the leftmost free block, until there are no gaps.
+    Blocks,
+    /// Part 2: move every file once, highest id first, to the leftmost free span that fits it,
+    /// if that's to the left of the file.
+    Files,
+}
+
+/// A disk being compacted. As an iterator, it yields the disk after every move.
+pub struct Compaction {
+    disk: Disk,
+    strategy: Strategy,
+    // No block before this one is free
+    first_free: usize,
+    // No block from this one on is used
+    last_used: usize,
+    // The next file to try to move, for `Strategy::Files`
+    next_file: Option<u64>,
+}
+
+impl Compaction {
+    pub fn disk(&self) -> &Disk {
+        &self.disk
+    }
+
+    /// Makes the next move. Returns false when the disk is fully compacted.
+    pub fn step(&mut self) -> bool {
+        match self.strategy {
+            Strategy::Blocks => self.move_block(),
+            Strategy::Files => {
+                while let Some(id) = self.next_file {
+                    self.next_file = id.checked_sub(1);
+                    if self.move_file(id) {
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    fn move_block(&mut self) -> bool {
+        let blocks = &mut self.disk.blocks;
+        while self.first_free < blocks.len() && blocks[self.first_free].is_some() {
+            self.first_free += 1;
+        }
+        while self.last_used > 0 && blocks[self.last_used - 1].is_none() {
+            self.last_used -= 1;
+        }
+        if self.first_free + 1 >= self.last_used {
+            return false;
+        }
+        blocks.swap(self.first_free, self.last_used - 1);
+        true
+    }
+
+    fn move_file(&mut self, id: u64) -> bool {
+        let blocks = &mut self.disk.blocks;
+        let Some(file) = blocks
+            .iter()
+            .rposition(|&block| block == Some(id))
+            .map(|end| {
+                let start = blocks[..end]
+                    .iter()
+                    .rposition(|&block| block != Some(id))
+                    .map_or(0, |before| before + 1);
+                start..end + 1
+            })
+        else {
+            return false;
+        };
+
+        // Find the leftmost free span that fits, left of the file
+        let mut span_start = self.first_free;
+        for position in self.first_free..file.start {
+            if blocks[position].is_some() {
+                span_start = position + 1;
+            } else if position + 1 - span_start == file.len() {
+                for offset in 0..file.len() {
+                    blocks.swap(span_start + offset, file.start + offset);
+                }
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Iterator for Compaction {
+    type Item = Disk;
+
+    fn next(&mut self) -> Option<Disk> {
+        self.step().then(|| self.disk.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{puzzle_part1, puzzle_part2};
+
+    fn disk(input: &str) -> Disk {
+        Disk::from(&PuzzleInput::try_from(input).unwrap())
+    }
+
+    #[rstest]
+    #[case::small("12345", "0..111....22222")]
+    #[case::example(
+        include_str!("../example_input.txt"),
+        "00...111...2...333.44.5555.6666.777.888899"
+    )]
+    fn test_render(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(disk(input).to_string(), expected);
+    }
+
+    #[test]
+    /// The steps the puzzle text shows for part 1.
+    fn test_compact_blocks_steps() {
+        let steps = disk("12345")
+            .compaction(Strategy::Blocks)
+            .map(|disk| disk.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            vec![
+                "02.111....2222.",
+                "022111....222..",
+                "0221112...22...",
+                "02211122..2....",
+                "022111222......",
+            ]
+        );
+    }
+
+    #[test]
+    /// The steps the puzzle text shows for part 2.
+    fn test_compact_files_steps() {
+        let steps = disk(include_str!("../example_input.txt"))
+            .compaction(Strategy::Files)
+            .map(|disk| disk.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            vec![
+                "0099.111...2...333.44.5555.6666.777.8888..",
+                "0099.1117772...333.44.5555.6666.....8888..",
+                "0099.111777244.333....5555.6666.....8888..",
+                "00992111777.44.333....5555.6666.....8888..",
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::example(include_str!("../example_input.txt"))]
+    #[case::space_at_end("22222")]
+    #[case::no_space("123")]
+    #[case::split_span("1911121")]
+    #[case::large_sizes("10 3 12 20 5 1 3 0 25 40 2")]
+    #[case::empty_file("1 3 0 1 2")]
+    #[case::empty_file_no_space("1 2 0 0 3")]
+    /// Both parts give the checksum of the compacted disk.
+    fn test_checksum(#[case] input: &str) {
+        let parsed = PuzzleInput::try_from(input).unwrap();
+        assert_eq!(
+            disk(input).compact(Strategy::Blocks).checksum().to_string(),
+            puzzle_part1::solve(&parsed)
+        );
+        assert_eq!(
+            disk(input).compact(Strategy::Files).checksum().to_string(),
+            puzzle_part2::solve(&parsed)
+        );
+    }
+
+    #[test]
+    fn test_final_layout() {
+        let example = disk(include_str!("../example_input.txt"));
+        assert_eq!(
+            example.clone().compact(Strategy::Blocks).to_string(),
+            "0099811188827773336446555566.............."
+        );
+        let compacted = example.compact(Strategy::Files);
+        assert_eq!(
+            compacted.to_string(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+        assert_eq!(compacted.files()[2], 4..5);
+    }
+}
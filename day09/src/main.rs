@@ -1,3 +1,4 @@
+pub mod disk;
 pub mod puzzle_input;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
@@ -10,9 +11,23 @@ fn main() {
         Err(e) => {
             println!("Error: {e:?}");
         }
-        Ok(input) => {
-            println!("day09 - part 1: {}", puzzle_part1::solve(&input));
-            println!("day09 - part 2: {}", puzzle_part2::solve(&input));
-        }
+        Ok(input) => match std::env::args().nth(1).as_deref() {
+            // Show every step of compacting the disk
+            Some("steps") => {
+                let strategy = match std::env::args().nth(2).as_deref() {
+                    Some("files") => disk::Strategy::Files,
+                    _ => disk::Strategy::Blocks,
+                };
+                let disk = disk::Disk::from(&input);
+                println!("{disk}");
+                for step in disk.compaction(strategy) {
+                    println!("{step}");
+                }
+            }
+            _ => {
+                println!("day09 - part 1: {}", puzzle_part1::solve(&input));
+                println!("day09 - part 2: {}", puzzle_part2::solve(&input));
+            }
+        },
     };
 }
@@ -1,17 +1,18 @@
 use nom::{
-    character::complete::{multispace0, one_of},
+    branch::alt,
+    character::complete::{self, multispace0, multispace1, one_of},
     combinator::{map, opt},
     error::{Error, ErrorKind},
     multi::many1,
-    sequence::{delimited, tuple},
+    sequence::{delimited, pair, preceded, tuple},
     Finish, IResult,
 };
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct File {
     pub index: u64,
-    pub size: u8,
-    pub space_after: u8,
+    pub size: u64,
+    pub space_after: u64,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -20,23 +21,39 @@ pub struct PuzzleInput {
 }
 
 fn parse_puzzle(input: &str) -> IResult<&str, PuzzleInput> {
-    let file_parser = map(
+    // The puzzle's format: a digit for each size
+    let dense_parser = many1(map(
         tuple((one_of("1234567890"), opt(one_of("1234567890")))),
-        |(sz, fr)| File {
-            index: 0u64, // To be filled by the parser below
-            size: sz.to_digit(10).unwrap() as u8,
-            space_after: fr.unwrap_or('0').to_digit(10).unwrap() as u8,
+        |(sz, fr)| {
+            (
+                sz.to_digit(10).unwrap() as u64,
+                fr.map(|fr| fr.to_digit(10).unwrap() as u64),
+            )
+        },
+    ));
+
+    // Whitespace-separated sizes, for files and spaces larger than 9 blocks. Takes at least two
+    // numbers, so a single number is read as a dense map.
+    let integer_parser = map(
+        pair(complete::u64, many1(preceded(multispace1, complete::u64))),
+        |(first, rest)| {
+            let sizes = std::iter::once(first).chain(rest).collect::<Vec<_>>();
+            sizes
+                .chunks(2)
+                .map(|chunk| (chunk[0], chunk.get(1).copied()))
+                .collect::<Vec<_>>()
         },
     );
 
     // Main parser for the puzzle
-    let puzzle_parser = map(many1(file_parser), |files| PuzzleInput {
+    let puzzle_parser = map(alt((integer_parser, dense_parser)), |files| PuzzleInput {
         files: files
-            .iter()
+            .into_iter()
             .enumerate()
-            .map(|(i, f)| File {
+            .map(|(i, (size, space_after))| File {
                 index: i as u64,
-                ..f.clone()
+                size,
+                space_after: space_after.unwrap_or(0),
             })
             .collect(),
     });
@@ -84,6 +101,23 @@ mod tests {
         assert!(!input.files.iter().any(|f| f.size == 0));
     }
 
+    #[rstest]
+    #[case::dense("12345", &[(1, 2), (3, 4), (5, 0)])]
+    #[case::integers("1 2 3 4 5", &[(1, 2), (3, 4), (5, 0)])]
+    #[case::large_sizes("12 0\n100000000000 34\n5\n", &[(12, 0), (100_000_000_000, 34), (5, 0)])]
+    #[case::single_number("12", &[(1, 2)])]
+    fn test_puzzle_input_formats(#[case] input: &str, #[case] expected: &[(u64, u64)]) {
+        let input = PuzzleInput::try_from(input).unwrap();
+        assert_eq!(
+            input
+                .files
+                .iter()
+                .map(|f| (f.size, f.space_after))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
     #[rstest]
     #[case::example_input("Not valid input")]
     #[case::final_input("")]
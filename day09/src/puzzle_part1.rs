@@ -1,73 +1,88 @@
-use std::collections::VecDeque;
-
 use crate::puzzle_input::{File, PuzzleInput};
 
-struct Defragger {
-    files: VecDeque<File>,
-    head_file: File,
-    tail_file: File,
+/// The sum of the block positions of a run of `len` blocks from `start`, times the file's index.
+pub(crate) fn run_checksum(start: u64, len: u64, index: u64) -> u128 {
+    let (start, len) = (start as u128, len as u128);
+    (start * len + len * len.saturating_sub(1) / 2) * index as u128
+}
+
+// Yields the compacted disk as runs of blocks of the same file, as (file index, length), from the
+// start of the disk. Free space is filled from the last file, in runs as large as both the space
+// and what's left of that file allow, so the work doesn't depend on the sizes.
+struct Defragger<'a> {
+    files: &'a [File],
+    // The first file that hasn't moved, and whether it's been yielded yet
+    head: usize,
+    head_yielded: bool,
+    // What's left of the space after the head file
+    space_left: u64,
+    // The file that's being moved into the spaces, and how many of its blocks haven't moved
+    tail: usize,
+    tail_left: u64,
 }
 
-impl Defragger {
-    fn new(files: &[File]) -> Self {
-        let mut files = files.iter().cloned().collect::<VecDeque<_>>();
-        let head_file = files.pop_front().unwrap();
-        let tail_file = files.pop_back().unwrap();
+impl<'a> Defragger<'a> {
+    fn new(files: &'a [File]) -> Self {
+        let tail = files.len() - 1;
         Self {
             files,
-            head_file,
-            tail_file,
+            head: 0,
+            head_yielded: false,
+            space_left: 0,
+            tail,
+            tail_left: files[tail].size,
         }
     }
 }
 
-impl Iterator for Defragger {
-    type Item = u64;
+impl Iterator for Defragger<'_> {
+    type Item = (u64, u64);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.head_file.size == 0 && self.head_file.space_after == 0 {
-            // We've consumed the current file, find the next one.
-            if let Some(f) = self.files.pop_front() {
-                self.head_file = f;
-            } else {
-                // No more files available, finish consuming the tail file.
-                self.head_file = self.tail_file.clone();
-                self.head_file.space_after = 0; // There's no space after we're done
-                self.tail_file.size = 0; // Tail file is transfered to head file
+        while self.head <= self.tail {
+            if !self.head_yielded {
+                let file = &self.files[self.head];
+                self.head_yielded = true;
+                self.space_left = file.space_after;
+                if self.head == self.tail {
+                    // The rest of the last file stays where it is, right after the moved blocks
+                    self.head += 1;
+                    return Some((file.index, self.tail_left));
+                }
+                return Some((file.index, file.size));
             }
-        }
-
-        if self.head_file.size > 0 {
-            // Currently in a file that's not moved.
-            self.head_file.size -= 1;
-            return Some(self.head_file.index);
-        }
 
-        if self.head_file.space_after > 0 {
-            // Currently in the space after an unmoved, file, fill it with the tail file.
-            self.head_file.space_after -= 1;
-            if self.tail_file.size == 0 {
-                match self.files.pop_back() {
-                    Some(f) => self.tail_file = f,
-                    None => {
-                        // All done!
-                        return None;
-                    }
+            if self.tail_left == 0 {
+                // The tail file has moved completely, continue with the one before it. Files can
+                // be empty, so that can be the head file, which already stays where it is.
+                self.tail -= 1;
+                if self.tail == self.head {
+                    return None;
                 }
+                self.tail_left = self.files[self.tail].size;
+            } else if self.space_left == 0 {
+                self.head += 1;
+                self.head_yielded = false;
+            } else {
+                let len = self.space_left.min(self.tail_left);
+                self.space_left -= len;
+                self.tail_left -= len;
+                return Some((self.files[self.tail].index, len));
             }
-            self.tail_file.size -= 1;
-            return Some(self.tail_file.index);
         }
-
         None
     }
 }
 
 pub fn solve(input: &PuzzleInput) -> String {
+    let mut position = 0;
     Defragger::new(&input.files)
-        .enumerate()
-        .map(|(i, f)| i as u64 * f)
-        .sum::<u64>()
+        .map(|(index, len)| {
+            let checksum = run_checksum(position, len, index);
+            position += len;
+            checksum
+        })
+        .sum::<u128>()
         .to_string()
 }
 
@@ -81,6 +96,10 @@ mod tests {
     #[case::example_input(include_str!("../example_input.txt"), "1928")]
     #[case::example_input("22222", "19")]
     #[case::final_input( include_str!("../input.txt"), "6446899523367")]
+    #[case::example_integers("2 3 3 3 1 3 3 1 2 1 4 1 4 1 3 1 4 0 2", "1928")]
+    #[case::single_file("5", "0")]
+    // Block by block, this takes billions of steps
+    #[case::huge_space("1 1 2000000000 0", "2000000001000000000")]
     fn test_solve(#[case] input: &str, #[case] expected: &str) {
         let input = PuzzleInput::try_from(input).unwrap();
         assert_eq!(solve(&input), expected);
@@ -1,11 +1,12 @@
 use std::{collections::BTreeMap, ops::Range};
 
 use crate::puzzle_input::PuzzleInput;
+use crate::puzzle_part1::run_checksum;
 
 struct PlacedFile {
     index: u64,
     start: u64,
-    len: u8,
+    len: u64,
 }
 
 struct UsedMap {
@@ -29,14 +30,14 @@ impl UsedMap {
         }
     }
 
-    fn find_free(&mut self, len: u8, max_pos: u64) -> Option<u64> {
+    fn find_free(&mut self, len: u64, max_pos: u64) -> Option<u64> {
         let mut cursor = 0;
         for (start, range) in self.used.iter() {
             if *start > max_pos {
                 return None;
             }
 
-            if range.start - cursor >= len as u64 {
+            if range.start - cursor >= len {
                 return Some(cursor);
             }
 
@@ -46,9 +47,71 @@ impl UsedMap {
     }
 }
 
-pub fn solve(input: &PuzzleInput) -> String {
+/// The free spans on the disk, in disk order, in a tree that holds the largest span under each node.
+/// Finding the leftmost span that fits a file is a walk down the tree, whatever the span sizes are.
+struct FreeIndex {
+    // Where each span starts
+    starts: Vec<u64>,
+    // The tree, with the root at 1 and the spans' sizes in the leaves, from `leaves` on
+    tree: Vec<u64>,
+    leaves: usize,
+}
+
+impl FreeIndex {
+    fn new(files: &[PlacedFile], input: &PuzzleInput) -> FreeIndex {
+        let starts = files
+            .iter()
+            .map(|placed| placed.start + placed.len)
+            .collect::<Vec<_>>();
+        let leaves = starts.len().next_power_of_two();
+        let mut tree = vec![0; 2 * leaves];
+        for (i, file) in input.files.iter().enumerate() {
+            tree[leaves + i] = file.space_after;
+        }
+        for node in (1..leaves).rev() {
+            tree[node] = tree[2 * node].max(tree[2 * node + 1]);
+        }
+        FreeIndex {
+            starts,
+            tree,
+            leaves,
+        }
+    }
+
+    /// Takes the leftmost free span that fits `len` blocks and starts before `max_pos`, and
+    /// returns where it starts. Whatever is left of the span stays in the index.
+    fn take(&mut self, len: u64, max_pos: u64) -> Option<u64> {
+        if self.tree[1] < len {
+            return None;
+        }
+        let mut node = 1;
+        while node < self.leaves {
+            node = if self.tree[2 * node] >= len {
+                2 * node
+            } else {
+                2 * node + 1
+            };
+        }
+
+        let span = node - self.leaves;
+        let start = self.starts[span];
+        if start >= max_pos {
+            return None;
+        }
+
+        self.starts[span] += len;
+        self.tree[node] -= len;
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = self.tree[2 * node].max(self.tree[2 * node + 1]);
+        }
+        Some(start)
+    }
+}
+
+fn place_files(input: &PuzzleInput) -> Vec<PlacedFile> {
     let mut cursor = 0u64;
-    let mut files = input
+    input
         .files
         .iter()
         .map(|f| {
@@ -57,38 +120,62 @@ pub fn solve(input: &PuzzleInput) -> String {
                 start: cursor,
                 len: f.size,
             };
-            cursor += (f.size + f.space_after) as u64;
+            cursor += f.size + f.space_after;
             result
         })
-        .collect::<Vec<_>>();
+        .collect::<Vec<_>>()
+}
+
+fn checksum(files: &[PlacedFile]) -> String {
+    files
+        .iter()
+        .map(|file| run_checksum(file.start, file.len, file.index))
+        .sum::<u128>()
+        .to_string()
+}
+
+/// The original implementation, which scans the used areas from the start of the disk for every
+/// file.
+pub fn solve_used_map(input: &PuzzleInput) -> String {
+    let mut files = place_files(input);
 
     let last_file = files.last().unwrap();
-    let disk_size = last_file.start + last_file.len as u64;
+    let disk_size = last_file.start + last_file.len;
 
     let mut free_map = UsedMap::new(disk_size);
     files.iter().for_each(|file| {
-        free_map.mark(file.start..(file.start + file.len as u64), true);
+        free_map.mark(file.start..(file.start + file.len), true);
     });
 
     files.iter_mut().rev().for_each(|file| {
         if let Some(spot) = free_map.find_free(file.len, file.start) {
             // Mark the previous location of the file as free
-            free_map.mark(file.start..(file.start + file.len as u64), false);
+            free_map.mark(file.start..(file.start + file.len), false);
 
             // Move the file to the free space.
             file.start = spot;
 
             // Mark the free space as used.
-            free_map.mark(file.start..(file.start + file.len as u64), true);
+            free_map.mark(file.start..(file.start + file.len), true);
         }
     });
 
-    // Calculate checksum
-    files
-        .iter()
-        .flat_map(|file| (file.start..file.start + file.len as u64).map(|i| i * file.index))
-        .sum::<u64>()
-        .to_string()
+    checksum(&files)
+}
+
+pub fn solve(input: &PuzzleInput) -> String {
+    let mut files = place_files(input);
+    let mut free_index = FreeIndex::new(&files, input);
+
+    // A file only ever moves left, past the files that are still to be moved, so the space it
+    // leaves behind is never of use to them
+    files.iter_mut().rev().for_each(|file| {
+        if let Some(spot) = free_index.take(file.len, file.start) {
+            file.start = spot;
+        }
+    });
+
+    checksum(&files)
 }
 
 #[cfg(test)]
@@ -100,8 +187,42 @@ mod tests {
     #[rstest]
     #[case::example_input(include_str!("../example_input.txt"), "2858")]
     #[case::final_input( include_str!("../input.txt"), "6478232739671")]
+    #[case::split_span("1911121", "10")]
+    #[case::example_integers("2 3 3 3 1 3 3 1 2 1 4 1 4 1 3 1 4 0 2", "2858")]
     fn test_solve(#[case] input: &str, #[case] expected: &str) {
         let input = PuzzleInput::try_from(input).unwrap();
         assert_eq!(solve(&input), expected);
+        assert_eq!(solve_used_map(&input), expected);
+    }
+
+    fn free_index(input: &str) -> FreeIndex {
+        let input = PuzzleInput::try_from(input).unwrap();
+        FreeIndex::new(&place_files(&input), &input)
+    }
+
+    #[test]
+    /// A span is taken from the start, one piece at a time.
+    fn test_free_index_pieces() {
+        // 0.........1.2..3
+        let mut index = free_index("1911121");
+        assert_eq!(index.take(2, 15), Some(1));
+        assert_eq!(index.take(3, 15), Some(3));
+        assert_eq!(index.take(4, 15), Some(6));
+        assert_eq!(index.take(1, 15), Some(11));
+        assert_eq!(index.take(1, 15), Some(13));
+        assert_eq!(index.take(1, 15), Some(14));
+        assert_eq!(index.take(1, 15), None);
+    }
+
+    #[test]
+    /// The leftmost span that fits, only if it starts before `max_pos`.
+    fn test_free_index_leftmost() {
+        // 0..1...2
+        let mut index = free_index("12131");
+        assert_eq!(index.take(4, 100), None);
+        assert_eq!(index.take(3, 4), None);
+        assert_eq!(index.take(3, 5), Some(4));
+        assert_eq!(index.take(2, 7), Some(1));
+        assert_eq!(index.take(1, 7), None);
     }
 }
@@ -1,6 +1,7 @@
 pub mod puzzle_input;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
+pub mod rules;
 
 fn main() {
     let puzzle_input = include_str!("../input.txt");
@@ -1,37 +1,51 @@
-use crate::puzzle_input::PuzzleInput;
+use aoc_memo::Memo;
 
-use memoize::memoize;
+use crate::puzzle_input::PuzzleInput;
+use crate::rules::{Evolution, Rules};
 
-#[memoize]
-fn count_expansion(input: u128, expansions: usize) -> u128 {
+fn count_expansion(memo: &mut Memo<(u128, usize), u128>, input: u128, expansions: usize) -> u128 {
     if expansions == 0 {
         return 1;
     }
 
-    if input == 0 {
-        return count_expansion(1, expansions - 1);
-    }
-
-    let digits = input.ilog10() + 1;
-    if digits % 2 == 0 {
-        let splitting_power = 10u128.pow(digits / 2);
-        let remainder = input % splitting_power;
-        let quotient = input / splitting_power;
-        count_expansion(remainder, expansions - 1) + count_expansion(quotient, expansions - 1)
-    } else {
-        count_expansion(input * 2024, expansions - 1)
-    }
+    memo.get_or_compute((input, expansions), |memo| {
+        if input == 0 {
+            return count_expansion(memo, 1, expansions - 1);
+        }
+
+        let digits = input.ilog10() + 1;
+        if digits.is_multiple_of(2) {
+            let splitting_power = 10u128.pow(digits / 2);
+            let remainder = input % splitting_power;
+            let quotient = input / splitting_power;
+            count_expansion(memo, remainder, expansions - 1)
+                + count_expansion(memo, quotient, expansions - 1)
+        } else {
+            count_expansion(memo, input * 2024, expansions - 1)
+        }
+    })
 }
 
-pub fn solve(input: &PuzzleInput) -> String {
+/// Counts the stones each stone expands into, one stone at a time, with the puzzle's rules built in.
+pub fn solve_memo(input: &PuzzleInput) -> String {
+    // A fresh cache per solve, so repeated runs (e.g. benchmarks) don't reuse earlier results
+    let mut memo = Memo::with_capacity(4096);
     input
         .numbers
         .iter()
-        .map(|number| count_expansion(*number, 75))
+        .map(|number| count_expansion(&mut memo, *number, 75))
         .sum::<u128>()
         .to_string()
 }
 
+pub fn solve(input: &PuzzleInput) -> String {
+    let rules = Rules::puzzle();
+    Evolution::new(&rules, &input.numbers)
+        .blink_until(75)
+        .stone_count()
+        .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -43,5 +57,6 @@ mod tests {
     fn test_solve(#[case] input: &str, #[case] expected: &str) {
         let input = PuzzleInput::try_from(input).unwrap();
         assert_eq!(solve(&input), expected);
+        assert_eq!(solve_memo(&input), expected);
     }
 }
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+pub type Stone = u128;
+
+/// A rule for what a stone turns into when blinking. Returns None if the rule doesn't apply to the
+/// stone.
+pub trait Rule {
+    fn apply(&self, stone: Stone) -> Option<Vec<Stone>>;
+}
+
+impl<F: Fn(Stone) -> Option<Vec<Stone>>> Rule for F {
+    fn apply(&self, stone: Stone) -> Option<Vec<Stone>> {
+        self(stone)
+    }
+}
+
+/// A stone engraved with 0 becomes a stone engraved with 1.
+pub struct ZeroToOne;
+
+impl Rule for ZeroToOne {
+    fn apply(&self, stone: Stone) -> Option<Vec<Stone>> {
+        (stone == 0).then(|| vec![1])
+    }
+}
+
+/// A stone with an even number of digits splits in two: the left half of the digits, and the
+/// right half.
+pub struct SplitEvenDigits;
+
+impl Rule for SplitEvenDigits {
+    fn apply(&self, stone: Stone) -> Option<Vec<Stone>> {
+        if stone == 0 {
+            return None;
+        }
+        let digits = stone.ilog10() + 1;
+        digits.is_multiple_of(2).then(|| {
+            let splitting_power = 10u128.pow(digits / 2);
+            vec![stone / splitting_power, stone % splitting_power]
+        })
+    }
+}
+
+/// Any stone is multiplied by the factor.
+pub struct Multiply(pub Stone);
+
+impl Rule for Multiply {
+    fn apply(&self, stone: Stone) -> Option<Vec<Stone>> {
+        Some(vec![stone * self.0])
+    }
+}
+
+/// The rules stones change by, in order: the first rule that applies to a stone decides what it
+/// turns into. A stone that no rule applies to stays as it is.
+#[derive(Default)]
+pub struct Rules {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Rules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The rules from the puzzle text.
+    pub fn puzzle() -> Self {
+        Rules::new()
+            .with(ZeroToOne)
+            .with(SplitEvenDigits)
+            .with(Multiply(2024))
+    }
+
+    /// Adds a rule, after the rules that are already there.
+    pub fn with(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn apply(&self, stone: Stone) -> Vec<Stone> {
+        self.rules
+            .iter()
+            .find_map(|rule| rule.apply(stone))
+            .unwrap_or_else(|| vec![stone])
+    }
+}
+
+/// The stones as they evolve, blink by blink. The order of the stones never matters for what they
+/// turn into, so only the number of stones with each engraving is kept.
+pub struct Evolution<'a> {
+    rules: &'a Rules,
+    histogram: HashMap<Stone, u128>,
+    blinks: usize,
+}
+
+impl<'a> Evolution<'a> {
+    pub fn new(rules: &'a Rules, stones: &[Stone]) -> Self {
+        let mut histogram = HashMap::new();
+        for &stone in stones {
+            *histogram.entry(stone).or_default() += 1;
+        }
+        Evolution {
+            rules,
+            histogram,
+            blinks: 0,
+        }
+    }
+
+    pub fn blink(&mut self) {
+        let mut histogram = HashMap::with_capacity(self.histogram.len());
+        for (&stone, &count) in &self.histogram {
+            for new_stone in self.rules.apply(stone) {
+                *histogram.entry(new_stone).or_default() += count;
+            }
+        }
+        self.histogram = histogram;
+        self.blinks += 1;
+    }
+
+    /// Blinks until `blinks` blinks have happened in total. Blinks can't be undone, so this does
+    /// nothing if there were that many already.
+    pub fn blink_until(&mut self, blinks: usize) -> &mut Self {
+        while self.blinks < blinks {
+            self.blink();
+        }
+        self
+    }
+
+    /// The number of blinks so far.
+    pub fn blinks(&self) -> usize {
+        self.blinks
+    }
+
+    /// How many stones there are with each engraving.
+    pub fn histogram(&self) -> &HashMap<Stone, u128> {
+        &self.histogram
+    }
+
+    pub fn stone_count(&self) -> u128 {
+        self.histogram.values().sum()
+    }
+
+    /// The number of different engravings on the stones.
+    pub fn distinct_stones(&self) -> usize {
+        self.histogram.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::zero(0, vec![1])]
+    #[case::even_digits(1000, vec![10, 0])]
+    #[case::odd_digits(1, vec![2024])]
+    fn test_puzzle_rules(#[case] stone: Stone, #[case] expected: Vec<Stone>) {
+        assert_eq!(Rules::puzzle().apply(stone), expected);
+    }
+
+    #[rstest]
+    // The arrangements from the puzzle text
+    #[case::one_blink(1, &[253000, 1, 7])]
+    #[case::two_blinks(2, &[253, 0, 2024, 14168])]
+    #[case::four_blinks(4, &[512, 72, 2024, 2, 0, 2, 4, 2867, 6032])]
+    #[case::six_blinks(
+        6,
+        &[2097446912, 14168, 4048, 2, 0, 2, 4, 40, 48, 2024, 40, 48, 80, 96, 2, 8, 6, 7, 6, 0, 3, 2]
+    )]
+    fn test_histogram(#[case] blinks: usize, #[case] stones: &[Stone]) {
+        let rules = Rules::puzzle();
+        let mut evolution = Evolution::new(&rules, &[125, 17]);
+        evolution.blink_until(blinks);
+
+        let expected = Evolution::new(&rules, stones);
+        assert_eq!(evolution.histogram(), expected.histogram());
+        assert_eq!(evolution.stone_count(), stones.len() as u128);
+        assert_eq!(evolution.blinks(), blinks);
+    }
+
+    #[test]
+    fn test_distinct_stones() {
+        let rules = Rules::puzzle();
+        let mut evolution = Evolution::new(&rules, &[125, 17]);
+        assert_eq!(evolution.blink_until(4).distinct_stones(), 8);
+        assert_eq!(evolution.blink_until(25).stone_count(), 55312);
+        // Going back does nothing
+        assert_eq!(evolution.blink_until(4).blinks(), 25);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        // Odd stones split into two even ones, even stones halve
+        let rules = Rules::new()
+            .with(|stone: Stone| (stone % 2 == 1).then(|| vec![stone - 1, stone + 1]))
+            .with(|stone: Stone| (stone > 0).then(|| vec![stone / 2]));
+        let mut evolution = Evolution::new(&rules, &[3]);
+        assert_eq!(
+            evolution.blink_until(2).histogram(),
+            &HashMap::from([(1, 1), (2, 1)])
+        );
+        // 0 stays 0, as no rule applies
+        assert_eq!(
+            evolution.blink_until(4).histogram(),
+            &HashMap::from([(0, 2), (1, 1), (2, 1)])
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use aoc_grid::Coordinate;
 use nom::{
     bytes::complete::tag,
     character::complete::{i64 as nom_i64, line_ending, multispace0, newline},
@@ -8,11 +9,8 @@ use nom::{
     Finish, IResult,
 };
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Vector {
-    pub x: i64,
-    pub y: i64,
-}
+// Button offsets and prize locations, which don't fit in a grid.
+pub type Vector = Coordinate<i64>;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ClawMachine {
@@ -44,9 +42,9 @@ fn parse_machine(input: &str) -> IResult<&str, ClawMachine> {
             )),
         )),
         |(a, b, t)| ClawMachine {
-            a: Vector { x: a.0, y: a.1 },
-            b: Vector { x: b.0, y: b.1 },
-            target: Vector { x: t.0, y: t.1 },
+            a: Vector::from(a),
+            b: Vector::from(b),
+            target: Vector::from(t),
         },
     )(input)
 }
@@ -7,12 +7,9 @@ pub fn solve(input: &PuzzleInput) -> String {
         .claw_machines
         .iter()
         .map(|m| ClawMachine {
-            a: m.a.clone(),
-            b: m.b.clone(),
-            target: Vector {
-                x: m.target.x + 10000000000000,
-                y: m.target.y + 10000000000000,
-            },
+            a: m.a,
+            b: m.b,
+            target: m.target + Vector::new(10000000000000, 10000000000000),
         })
         .flat_map(|m| m.solve())
         .map(|(a, b)| 3 * a + b)
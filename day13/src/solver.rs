@@ -1,50 +1,75 @@
+use aoc_math::linalg::{solve_integer_2x2, Solution};
+use aoc_math::LinearDiophantine;
+
 use crate::puzzle_input::ClawMachine;
 
+// Pressing A costs 3 tokens, pressing B costs 1 token.
+const COST_A: i64 = 3;
+const COST_B: i64 = 1;
+
 pub trait Solver {
     fn solve(&self) -> Option<(i64, i64)>;
 }
 
 impl Solver for ClawMachine {
     fn solve(&self) -> Option<(i64, i64)> {
-        // Solve this system of equations for Sa and Sb (assuming all variables != 0, so no degenerate cases):
+        // Solve this system of equations for Sa and Sb:
         //    Sa*Ax + Sb*Bx = Tx
         //    Sa*Ay + Sb*By = Ty
-        // Solve for Sa:
-        //    Sa = (Tx - Sb*Bx) / Ax
-        // Substitute Sa in the second equation
-        //    (Tx/Ax - Sb*Bx/Ax)*Ay + Sb*By = Ty
-        //    Tx*Ay/Ax - Sb*Bx*Ay/Ax + Sb*By = Ty
-        // Multiply by Ax to get rid of the division
-        //    Tx*Ay - Sb*Bx*Ay + Sb*By*Ax = Ty*Ax
-        //    Sb*By*Ax - Sb*Bx*Ay =  Ty*Ax - Tx*Ay
-        //    Sb*(By*Ax - Bx*Ay) = Ty*Ax - Tx*Ay
-        // Solve for Sb
-        //   Sb = (Ty*Ax - Tx*Ay) / (By*Ax - Bx*Ay)
-        // Then solve for Sa
-        //    Sa = (Tx - Sb*Bx) / Ax
-
-        // A solution exists iff (By*Ax - Bx*Ay) divides (Tx*Ay - Ty*Ax)
-
-        let det = self.b.y * self.a.x - self.b.x * self.a.y;
-        assert!(det != 0);
-
-        if (self.target.x * self.a.y - self.target.y * self.a.x) % det != 0 {
-            return None;
+        let matrix = [[self.a.0, self.b.0], [self.a.1, self.b.1]];
+        match solve_integer_2x2(&matrix, [self.target.0, self.target.1]) {
+            // Buttons can't be pressed a negative number of times.
+            Solution::Unique([a, b]) if a >= 0 && b >= 0 => Some((a, b)),
+            Solution::Unique(_) | Solution::Inconsistent => None,
+            Solution::Infinite => solve_collinear(self),
         }
+    }
+}
 
-        let b = (self.target.y * self.a.x - self.target.x * self.a.y) / det;
-        if (self.target.x - b * self.b.x) % self.a.x != 0 {
-            // Division won't find an integer solution.
-            return None;
-        }
-        let a = (self.target.x - b * self.b.x) / self.a.x;
+// Both buttons (and the prize) lie on the same line, so there may be many ways to reach the
+// prize. Solve for a single axis, and pick the cheapest combination.
+fn solve_collinear(machine: &ClawMachine) -> Option<(i64, i64)> {
+    let (a, b, target) = if machine.a.0 != 0 || machine.b.0 != 0 {
+        (machine.a.0, machine.b.0, machine.target.0)
+    } else if machine.a.1 != 0 || machine.b.1 != 0 {
+        (machine.a.1, machine.b.1, machine.target.1)
+    } else {
+        // Neither button moves the claw. The system is only consistent if the prize is at the
+        // origin, which requires no presses.
+        return Some((0, 0));
+    };
+
+    LinearDiophantine::solve(a, b, target)?.min_cost_non_negative(COST_A, COST_B)
+}
 
-        let solution_xy = (a, b);
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
 
-        // Verify the solution satisfied both axes.
-        assert!(solution_xy.0 * self.a.x + solution_xy.1 * self.b.x == self.target.x);
-        assert!(solution_xy.0 * self.a.y + solution_xy.1 * self.b.y == self.target.y);
+    use super::*;
+    use crate::puzzle_input::Vector;
 
-        Some(solution_xy)
+    #[rstest]
+    #[case::example(Vector::new(94, 34), Vector::new(22, 67), Vector::new(8400, 5400), Some((80, 40)))]
+    #[case::no_solution(
+        Vector::new(26, 66),
+        Vector::new(67, 21),
+        Vector::new(12748, 12176),
+        None
+    )]
+    #[case::negative_presses(Vector::new(1, 0), Vector::new(0, 1), Vector::new(-1, 1), None)]
+    #[case::collinear_prefer_b(Vector::new(2, 2), Vector::new(1, 1), Vector::new(10, 10), Some((0, 10)))]
+    #[case::collinear_prefer_a(Vector::new(4, 4), Vector::new(1, 1), Vector::new(10, 10), Some((2, 2)))]
+    #[case::collinear_unreachable(Vector::new(2, 2), Vector::new(4, 4), Vector::new(5, 5), None)]
+    #[case::collinear_off_line(Vector::new(2, 2), Vector::new(4, 4), Vector::new(6, 7), None)]
+    #[case::vertical(Vector::new(0, 3), Vector::new(0, 1), Vector::new(0, 7), Some((0, 7)))]
+    fn test_solve(
+        #[case] a: Vector,
+        #[case] b: Vector,
+        #[case] target: Vector,
+        #[case] expected: Option<(i64, i64)>,
+    ) {
+        let machine = ClawMachine { a, b, target };
+        assert_eq!(machine.solve(), expected);
     }
 }
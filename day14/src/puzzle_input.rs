@@ -1,3 +1,4 @@
+use aoc_grid::Coordinate;
 use nom::{
     self,
     bytes::complete::tag,
@@ -11,8 +12,8 @@ use nom::{
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Robot {
-    pub position: (i64, i64),
-    pub velocity: (i64, i64),
+    pub position: Coordinate<i64>,
+    pub velocity: Coordinate<i64>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -28,7 +29,10 @@ fn parse_puzzle(input: &str) -> IResult<&str, PuzzleInput> {
             space1,
             preceded(tag("v="), separated_pair(i64, tag(","), i64)),
         ),
-        |(position, velocity)| Robot { position, velocity },
+        |(position, velocity)| Robot {
+            position: Coordinate::from(position),
+            velocity: Coordinate::from(velocity),
+        },
     );
 
     // Main parser for the puzzle
@@ -1,20 +1,19 @@
+use aoc_grid::Coordinate;
+
 use crate::puzzle_input::{PuzzleInput, Robot};
 
 trait RobotSimulator {
-    fn simulate(&self, seconds: i64, grid_size: (i64, i64)) -> (i64, i64);
+    fn simulate(&self, seconds: i64, grid_size: Coordinate<i64>) -> Coordinate<i64>;
 }
 
 impl RobotSimulator for Robot {
-    fn simulate(&self, seconds: i64, grid_size: (i64, i64)) -> (i64, i64) {
-        (
-            (self.position.0 + self.velocity.0 * seconds).rem_euclid(grid_size.0),
-            (self.position.1 + self.velocity.1 * seconds).rem_euclid(grid_size.1),
-        )
+    fn simulate(&self, seconds: i64, grid_size: Coordinate<i64>) -> Coordinate<i64> {
+        (self.position + self.velocity * seconds).rem_euclid(grid_size)
     }
 }
 
 pub fn solve(input: &PuzzleInput) -> String {
-    let grid_size = (
+    let grid_size = Coordinate::new(
         input
             .robots
             .iter()
@@ -42,7 +41,7 @@ pub fn solve(input: &PuzzleInput) -> String {
         .iter()
         // Simulate the robots
         .map(|robot| robot.simulate(100, grid_size))
-        .filter_map(|(x, y)| {
+        .filter_map(|Coordinate(x, y)| {
             // Compute tje quadrant for each robot
             if x < center.0 && y < center.1 {
                 //                println!("Q0 {},{}", x, y);
@@ -85,14 +84,14 @@ mod tests {
     }
 
     #[rstest]
-    #[case::example_robot(Robot{position: (2, 4), velocity: (2, -3)}, 1, (4, 1))]
-    #[case::example_robot(Robot{position: (2, 4), velocity: (2, -3)}, 2, (6, 5))]
+    #[case::example_robot(Robot{position: Coordinate(2, 4), velocity: Coordinate(2, -3)}, 1, Coordinate(4, 1))]
+    #[case::example_robot(Robot{position: Coordinate(2, 4), velocity: Coordinate(2, -3)}, 2, Coordinate(6, 5))]
     fn test_robot_simulate(
         #[case] robot: Robot,
         #[case] seconds: i64,
-        #[case] expected: (i64, i64),
+        #[case] expected: Coordinate<i64>,
     ) {
-        assert_eq!(robot.simulate(seconds, (11, 7)), expected);
+        assert_eq!(robot.simulate(seconds, Coordinate(11, 7)), expected);
     }
 
     #[test]
@@ -122,11 +121,11 @@ mod tests {
         let locations = puzzle
             .robots
             .iter()
-            .map(|robot| robot.simulate(100, (11, 7)));
+            .map(|robot| robot.simulate(100, Coordinate(11, 7)));
 
         let mut computed_location_grid = Grid::<i32>::new(11, 7);
-        for (x, y) in locations {
-            computed_location_grid[(x, y)] += 1;
+        for location in locations {
+            computed_location_grid[location] += 1;
         }
         dbg!(computed_location_grid.map(|c| c.to_string().chars().next().unwrap()));
         assert!(reference_location_grid == computed_location_grid);
@@ -1,40 +1,52 @@
-use std::vec;
+use aoc_grid::Coordinate;
+use aoc_math::crt;
 
 use crate::puzzle_input::{PuzzleInput, Robot};
 
 trait RobotSimulator {
-    fn simulate_step(&mut self, seconds: i64, grid_size: (i64, i64));
+    fn simulate(&self, seconds: i64, grid_size: Coordinate<i64>) -> Coordinate<i64>;
 }
 
 impl RobotSimulator for Robot {
-    fn simulate_step(&mut self, seconds: i64, grid_size: (i64, i64)) {
-        self.position.0 = (self.position.0 + self.velocity.0 * seconds).rem_euclid(grid_size.0);
-        self.position.1 = (self.position.1 + self.velocity.1 * seconds).rem_euclid(grid_size.1);
+    fn simulate(&self, seconds: i64, grid_size: Coordinate<i64>) -> Coordinate<i64> {
+        (self.position + self.velocity * seconds).rem_euclid(grid_size)
     }
 }
 
-fn contains_ascii_art(
-    robots: &mut impl Iterator<Item = (i64, i64)>,
-    grid_size: (i64, i64),
-) -> bool {
+// Ascii art has lines of robots, so some row or column will have many more robots than average.
+// Returns the size of the fullest row or column, if it stands out.
+fn ascii_art_line(positions: impl Iterator<Item = i64>, size: i64) -> Option<i64> {
     let mut robot_count = 0;
+    let tally = positions.fold(vec![0; size as usize], |mut tally, p| {
+        robot_count += 1;
+        tally[p as usize] += 1;
+        tally
+    });
 
-    let tally = robots.fold(
-        (vec![0; grid_size.0 as usize], vec![0; grid_size.1 as usize]),
-        |mut tally, (x, y)| {
-            robot_count += 1;
-            tally.0[x as usize] += 1;
-            tally.1[y as usize] += 1;
-            tally
-        },
-    );
+    let fullest = *tally.iter().max().unwrap();
+    (fullest > 5 * robot_count / size).then_some(fullest)
+}
 
-    tally.0.iter().any(|&v| v > 5 * robot_count / grid_size.0)
-        && tally.1.iter().any(|&v| v > 5 * robot_count / grid_size.1)
+// Finds the time (within one period of the axis) where the robots cluster the most on that axis.
+fn clustered_time(
+    robots: &[Robot],
+    grid_size: Coordinate<i64>,
+    axis: impl Fn(Coordinate<i64>) -> i64,
+) -> Option<i64> {
+    let period = axis(grid_size);
+    (0..period)
+        .filter_map(|seconds| {
+            let positions = robots
+                .iter()
+                .map(|robot| axis(robot.simulate(seconds, grid_size)));
+            ascii_art_line(positions, period).map(|fullest| (fullest, seconds))
+        })
+        .max()
+        .map(|(_, seconds)| seconds)
 }
 
 pub fn solve(input: &PuzzleInput) -> String {
-    let grid_size = (
+    let grid_size = Coordinate::new(
         input
             .robots
             .iter()
@@ -56,18 +68,18 @@ pub fn solve(input: &PuzzleInput) -> String {
         return "UNSOLVABLE".to_string();
     }
 
-    let mut robots = input.robots.clone();
-    for i in 0..10000 {
-        let mut robot_locations = robots.iter_mut().map(|robot| {
-            robot.simulate_step(1, grid_size);
-            robot.position
-        });
+    // The x positions of all robots repeat every `width` seconds, and the y positions every
+    // `height` seconds. Find the picture on each axis separately, and combine the two with the
+    // chinese remainder theorem.
+    let time_x = clustered_time(&input.robots, grid_size, |c| c.0);
+    let time_y = clustered_time(&input.robots, grid_size, |c| c.1);
 
-        if contains_ascii_art(&mut robot_locations, grid_size) {
-            return (i + 1).to_string();
-        }
+    match (time_x, time_y) {
+        (Some(time_x), Some(time_y)) => crt(&[(time_x, grid_size.0), (time_y, grid_size.1)])
+            .map(|(seconds, _)| seconds.to_string())
+            .unwrap_or_else(|| "UNSOLVABLE".to_string()),
+        _ => "UNSOLVABLE".to_string(),
     }
-    unreachable!()
 }
 
 #[cfg(test)]
@@ -83,4 +95,49 @@ mod tests {
         let input = PuzzleInput::try_from(input).unwrap();
         assert_eq!(solve(&input), expected);
     }
+
+    #[rstest]
+    #[case(7861)]
+    #[case(42)]
+    #[case(10402)]
+    /// Draws a frame at the given time, surrounded by noise, and verifies it's found.
+    fn test_solve_synthetic(#[case] picture_time: i64) {
+        let grid_size = Coordinate(101, 103);
+
+        // Simple deterministic pseudo random numbers
+        let mut seed = 0x2024u64;
+        let mut random = move |max: i64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) % max as u64) as i64
+        };
+
+        let frame = (40..71)
+            .flat_map(|x| [Coordinate(x, 30), Coordinate(x, 62)])
+            .chain((31..62).flat_map(|y| [Coordinate(40, y), Coordinate(70, y)]));
+        let noise = (0..400)
+            .map(|_| Coordinate(random(grid_size.0), random(grid_size.1)))
+            .collect::<Vec<_>>();
+
+        let mut robots = frame
+            .chain(noise)
+            .map(|target| {
+                let velocity = Coordinate(random(201) - 100, random(207) - 103);
+                Robot {
+                    position: (target - velocity * picture_time).rem_euclid(grid_size),
+                    velocity,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Make sure the grid size can be derived from the robot positions
+        robots.push(Robot {
+            position: grid_size - Coordinate(1, 1),
+            velocity: Coordinate(0, 0),
+        });
+
+        let input = PuzzleInput { robots };
+        assert_eq!(solve(&input), picture_time.to_string());
+    }
 }
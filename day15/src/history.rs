@@ -0,0 +1,364 @@
+use std::fmt::Display;
+
+use aoc_grid::{Coordinate, Direction};
+
+use crate::puzzle::{direction_char, GridElement, Move, PuzzleInput};
+
+impl PuzzleInput {
+    /// Reverts a turn taken by `take_turn`. Moves must be undone in reverse order.
+    pub fn undo_move(&mut self, m: &Move) {
+        // Clear all destinations first, as a destination can be the source of another cell
+        for &(c, _) in &m.displaced {
+            self.grid[c + &m.direction] = GridElement::Empty;
+        }
+        for &(c, element) in &m.displaced {
+            self.grid[c] = element;
+        }
+        self.robots[m.robot_index].position = m.robot;
+        self.turn -= 1;
+    }
+
+    /// Makes an undone move again, without recomputing which cells it pushes.
+    pub fn redo_move(&mut self, m: &Move) {
+        for &(c, _) in &m.displaced {
+            self.grid[c] = GridElement::Empty;
+        }
+        for &(c, element) in &m.displaced {
+            self.grid[c + &m.direction] = element;
+        }
+        if m.moved {
+            self.robots[m.robot_index].position = m.robot + &m.direction;
+        }
+        self.turn += 1;
+    }
+}
+
+/// The simulation, with every move it made so far, so it can be rewound and replayed.
+#[derive(Debug, Clone)]
+pub struct History {
+    state: PuzzleInput,
+    moves: Vec<Move>,
+    // The number of moves in `moves` that are applied to `state`
+    step: usize,
+}
+
+impl History {
+    pub fn new(puzzle: PuzzleInput) -> Self {
+        History {
+            state: puzzle,
+            moves: vec![],
+            step: 0,
+        }
+    }
+
+    pub fn state(&self) -> &PuzzleInput {
+        &self.state
+    }
+
+    /// The number of moves made to get to the current state.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Every move simulated so far, including undone ones.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The move that led to the current state.
+    pub fn last_move(&self) -> Option<&Move> {
+        self.step.checked_sub(1).map(|step| &self.moves[step])
+    }
+
+    /// Reverts the last move, returning it. Returns None at the start.
+    pub fn undo(&mut self) -> Option<&Move> {
+        self.step = self.step.checked_sub(1)?;
+        let m = &self.moves[self.step];
+        self.state.undo_move(m);
+        self.state.instructions.push_front(m.direction);
+        Some(m)
+    }
+
+    /// Makes the next move, replaying it if it was undone before. Returns None when there are no
+    /// instructions left.
+    pub fn redo(&mut self) -> Option<&Move> {
+        if self.step < self.moves.len() {
+            self.state.redo_move(&self.moves[self.step]);
+            self.state.instructions.pop_front();
+        } else if self.state.instructions.is_empty() {
+            return None;
+        } else {
+            let m = self.state.simulate_one_step();
+            self.moves.push(m);
+        }
+        self.step += 1;
+        self.last_move()
+    }
+
+    /// Makes a move that isn't in the instructions. Moves that were undone are forgotten, the
+    /// remaining instructions are kept.
+    pub fn play(&mut self, direction: Direction) -> &Move {
+        for _ in self.step..self.moves.len() {
+            self.state.instructions.pop_front();
+        }
+        self.moves.truncate(self.step);
+
+        let m = self.state.take_turn(direction);
+        self.moves.push(m);
+        self.step += 1;
+        &self.moves[self.step - 1]
+    }
+
+    /// The directions of the moves made to get to the current state.
+    pub fn directions(&self) -> impl Iterator<Item = Direction> + '_ {
+        self.moves[..self.step].iter().map(|m| m.direction)
+    }
+
+    /// Undoes or redoes moves until `step` moves are made, or the instructions run out. Returns
+    /// the step it ended at.
+    pub fn jump_to(&mut self, step: usize) -> usize {
+        while self.step > step {
+            self.undo();
+        }
+        while self.step < step && self.redo().is_some() {}
+        self.step
+    }
+
+    pub fn run_to_end(&mut self) {
+        while self.redo().is_some() {}
+    }
+
+    /// The state after `step` moves, if that many moves were simulated.
+    pub fn state_at(&self, step: usize) -> Option<PuzzleInput> {
+        if step > self.moves.len() {
+            return None;
+        }
+        let mut state = self.state.clone();
+        for m in self.moves[step.min(self.step)..self.step].iter().rev() {
+            state.undo_move(m);
+            state.instructions.push_front(m.direction);
+        }
+        for m in &self.moves[self.step..step.max(self.step)] {
+            state.redo_move(m);
+            state.instructions.pop_front();
+        }
+        Some(state)
+    }
+
+    /// The differences between the states after `from` and `to` moves.
+    pub fn diff(&self, from: usize, to: usize) -> Option<Diff> {
+        Some(Diff::new(&self.state_at(from)?, &self.state_at(to)?))
+    }
+}
+
+/// The differences between two states of the same warehouse.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diff {
+    /// The robots that moved, with their positions before and after.
+    pub robots: Vec<(usize, Coordinate, Coordinate)>,
+    /// Cells that changed, with their contents before and after.
+    pub cells: Vec<(Coordinate, GridElement, GridElement)>,
+}
+
+impl Diff {
+    pub fn new(before: &PuzzleInput, after: &PuzzleInput) -> Self {
+        assert_eq!(
+            (before.grid.width(), before.grid.height()),
+            (after.grid.width(), after.grid.height()),
+            "Can only compare states of the same warehouse"
+        );
+        let cells = before
+            .grid
+            .iter_pairs()
+            .filter(|&(c, e)| after.grid[c] != *e)
+            .map(|(c, e)| (c, *e, after.grid[c]))
+            .collect();
+        let robots = before
+            .robots
+            .iter()
+            .zip(&after.robots)
+            .enumerate()
+            .filter(|(_, (b, a))| b.position != a.position)
+            .map(|(index, (b, a))| (index, b.position, a.position))
+            .collect();
+        Diff { robots, cells }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.robots.is_empty() && self.cells.is_empty()
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for &(index, before, after) in &self.robots {
+            writeln!(f, "robot {index}: {before:?} -> {after:?}")?;
+        }
+        for &(c, before, after) in &self.cells {
+            writeln!(f, "{c:?}: {} -> {}", char::from(before), char::from(after))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = direction_char(self.direction);
+        match (self.moved, self.displaced.len()) {
+            (false, _) => write!(f, "{direction} blocked at {:?}", self.robot),
+            (true, 0) => write!(f, "{direction} from {:?}", self.robot),
+            (true, n) => write!(f, "{direction} from {:?}, pushing {n} cells", self.robot),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    // A wide box push that moves a stack of three boxes
+    const WIDE_PUSH: &str = concat!(
+        "########\n", //
+        "#......#\n", //
+        "#.[][].#\n", //
+        "#..[]..#\n", //
+        "#..@...#\n", //
+        "########\n", //
+        "\n",         //
+        "^^<^"        //
+    );
+
+    // Two robots taking turns, one of them pushing a box into the other
+    const TWO_ROBOTS: &str = concat!(
+        "#######\n", //
+        "#.@...#\n", //
+        "#..O@.#\n", //
+        "#######\n", //
+        "\n",        //
+        "<<v<><"     //
+    );
+
+    #[rstest]
+    #[case::example_input1(include_str!("../example_input1.txt"))]
+    #[case::example_input2(include_str!("../example_input2.txt"))]
+    #[case::wide_push(WIDE_PUSH)]
+    #[case::two_robots(TWO_ROBOTS)]
+    /// Undoing every move must give back the initial state, and redoing them the final one.
+    fn test_undo_redo_all(#[case] input: &str) {
+        let initial = PuzzleInput::try_from(input).unwrap();
+        let mut expected_final = initial.clone();
+        expected_final.simulate_all();
+
+        let mut history = History::new(initial.clone());
+        history.run_to_end();
+        assert_eq!(history.state(), &expected_final);
+        let steps = history.step();
+
+        while history.undo().is_some() {}
+        assert_eq!(history.state(), &initial);
+        assert_eq!(history.jump_to(steps), steps);
+        assert_eq!(history.state(), &expected_final);
+    }
+
+    #[test]
+    fn test_wide_push() {
+        let mut history = History::new(PuzzleInput::try_from(WIDE_PUSH).unwrap());
+
+        let m = history.redo().unwrap().clone();
+        assert_eq!(m.to_string(), "^ from (3, 4), pushing 6 cells");
+        assert_eq!(
+            history.state().to_string(),
+            concat!(
+                "########\n", //
+                "#.[][].#\n", //
+                "#..[]..#\n", //
+                "#..@...#\n", //
+                "#......#\n", //
+                "########\n", //
+                "\n",         //
+                "^<^"         //
+            )
+        );
+
+        // The wall blocks the next push
+        assert_eq!(history.redo().unwrap().to_string(), "^ blocked at (3, 3)");
+        assert_eq!(history.redo().unwrap().to_string(), "< from (3, 3)");
+        assert_eq!(history.step(), 3);
+
+        let third = history.moves()[2].clone();
+        assert_eq!(history.undo(), Some(&third));
+        assert_eq!(history.undo().unwrap().to_string(), "^ blocked at (3, 3)");
+        assert_eq!(history.undo(), Some(&m));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.state().to_string(), WIDE_PUSH);
+    }
+
+    #[test]
+    fn test_jump_to() {
+        let puzzle = PuzzleInput::try_from(include_str!("../example_input2.txt")).unwrap();
+        let mut history = History::new(puzzle.clone());
+
+        assert_eq!(history.jump_to(5), 5);
+        let mut expected = puzzle.clone();
+        (0..5).for_each(|_| {
+            expected.simulate_one_step();
+        });
+        assert_eq!(history.state(), &expected);
+
+        assert_eq!(history.jump_to(2), 2);
+        assert_eq!(history.state_at(5), Some(expected));
+        assert_eq!(history.state_at(0), Some(puzzle));
+        assert_eq!(history.state_at(6), None);
+
+        // Past the end of the instructions
+        assert_eq!(history.jump_to(1000), 15);
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut history = History::new(PuzzleInput::try_from(WIDE_PUSH).unwrap());
+        history.run_to_end();
+
+        assert!(history.diff(1, 2).unwrap().is_empty());
+        assert_eq!(
+            history.diff(0, 1).unwrap().to_string(),
+            "robot 0: (3, 4) -> (3, 3)
+(2, 1): . -> [
+(3, 1): . -> ]
+(4, 1): . -> [
+(5, 1): . -> ]
+(2, 2): [ -> .
+(3, 2): ] -> [
+(4, 2): [ -> ]
+(5, 2): ] -> .
+(3, 3): [ -> .
+(4, 3): ] -> .
+"
+        );
+        assert_eq!(history.diff(0, 5), None);
+    }
+
+    #[test]
+    fn test_play() {
+        let mut history = History::new(PuzzleInput::try_from(WIDE_PUSH).unwrap());
+        history.redo();
+        history.redo();
+        history.undo();
+
+        // Replaces the undone ^, keeps the rest of the instructions
+        assert_eq!(history.play(Direction::Right).to_string(), "> from (3, 3)");
+        assert_eq!(history.moves().len(), 2);
+        assert_eq!(
+            history.directions().collect::<Vec<_>>(),
+            vec![Direction::Up, Direction::Right]
+        );
+        assert_eq!(
+            history.state().instructions,
+            [Direction::Left, Direction::Up]
+        );
+        assert_eq!(history.redo().unwrap().to_string(), "< from (4, 3)");
+    }
+}
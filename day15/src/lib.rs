@@ -1,4 +1,7 @@
+pub mod history;
+pub mod play;
 pub mod puzzle;
 pub mod puzzle_parse;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
+pub mod solver;
@@ -1,12 +1,48 @@
 use puzzle_part1::Part1;
 use puzzle_part2::Part2;
 
+pub mod history;
+pub mod play;
 pub mod puzzle;
 pub mod puzzle_parse;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
+pub mod solver;
+
+// Finds the shortest way to put the boxes on the goals marked in a puzzle file, or to get the
+// highest GPS coordinate sum if there are none
+fn solve(path: &str) {
+    let input = std::fs::read_to_string(path).expect("could not read the puzzle");
+    let (input, goals) = solver::split_goals(&input);
+    let target = if goals.is_empty() {
+        solver::Target::MaximumCoordinateSum
+    } else {
+        solver::Target::Goals(goals)
+    };
+    match puzzle::PuzzleInput::try_from(input.as_str()) {
+        Err(e) => println!("Error: {e:?}"),
+        Ok(puzzle) => match solver::Solver::new(target).solve(&puzzle) {
+            None => println!("no solution found"),
+            Some(solution) => {
+                println!("{solution}");
+                println!(
+                    "{} moves, GPS sum: {}, {} states visited",
+                    solution.moves.len(),
+                    solution.coordinate_sum,
+                    solution.visited
+                );
+            }
+        },
+    }
+}
 
 fn main() {
+    if std::env::args().nth(1).as_deref() == Some("solve") {
+        let path = std::env::args().nth(2).expect("usage: day15 solve <file>");
+        solve(&path);
+        return;
+    }
+
     let puzzle_input = include_str!("../input.txt");
 
     // Solve the puzzle for each input
@@ -14,6 +50,16 @@ fn main() {
         Err(e) => {
             println!("Error: {e:?}");
         }
+        Ok(input) if std::env::args().nth(1).as_deref() == Some("play") => {
+            // `play wide` plays the part 2 warehouse
+            let input = match std::env::args().nth(2).as_deref() {
+                Some("wide") => input.expand(),
+                _ => input,
+            };
+            play::Game::new(&input)
+                .run_interactive(std::io::stdin().lock(), std::io::stdout())
+                .expect("game I/O failed");
+        }
         Ok(input) => {
             println!("day15 - part 1: {}", input.part1());
             println!("day15 - part 2: {}", input.part2());
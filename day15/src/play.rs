@@ -0,0 +1,255 @@
+use std::io::{BufRead, Write};
+
+use aoc_grid::Direction;
+
+use crate::history::History;
+use crate::puzzle::{direction_char, PuzzleInput, Robot};
+
+const HELP: &str = "\
+commands:
+  ^ v < >        move the robot; several moves can go on one line
+  arrow keys     move the robot (press enter after them)
+  u, undo [n]    take back n moves (default 1)
+  r, redo [n]    make n undone moves again (default 1)
+  m, moves       show the moves so far, in the puzzle's instruction format
+  save <file>    save the warehouse and the moves as a puzzle input
+  h, help        show this help
+  q, quit        stop playing";
+
+/// Parses moves typed as `^v<>`, or as the escape sequences terminals send for the arrow keys.
+pub fn parse_moves(line: &str) -> Result<Vec<Direction>, String> {
+    let mut moves = Vec::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        let direction = match c {
+            '^' => Direction::Up,
+            'v' => Direction::Down,
+            '<' => Direction::Left,
+            '>' => Direction::Right,
+            // ESC [ A or ESC O A, depending on the terminal mode
+            '\x1b' => match (chars.next(), chars.next()) {
+                (Some('[' | 'O'), Some('A')) => Direction::Up,
+                (Some('[' | 'O'), Some('B')) => Direction::Down,
+                (Some('[' | 'O'), Some('C')) => Direction::Right,
+                (Some('[' | 'O'), Some('D')) => Direction::Left,
+                _ => return Err("unsupported key".to_string()),
+            },
+            c if c.is_whitespace() => continue,
+            c => return Err(format!("invalid move {c}")),
+        };
+        moves.push(direction);
+    }
+    Ok(moves)
+}
+
+/// The warehouse, driven by a human instead of by the instructions.
+pub struct Game {
+    pub history: History,
+}
+
+impl Game {
+    /// Starts a game in the warehouse of `puzzle`, ignoring its instructions.
+    pub fn new(puzzle: &PuzzleInput) -> Self {
+        let mut puzzle = puzzle.clone();
+        puzzle.instructions.clear();
+        Game {
+            history: History::new(puzzle),
+        }
+    }
+
+    /// The map, and the GPS coordinate sum that part 1 and part 2 ask for.
+    pub fn status(&self) -> String {
+        let state = self.history.state();
+        let mut map = state.grid.map(|c| char::from(*c));
+        for robot in &state.robots {
+            map[robot.position] = Robot::symbol(robot.strength);
+        }
+        format!(
+            "{map}moves: {}  GPS sum: {}",
+            self.history.step(),
+            state.coordinate_sum()
+        )
+    }
+
+    /// The moves made so far, in the puzzle's instruction format.
+    pub fn moves(&self) -> String {
+        self.history.directions().map(direction_char).collect()
+    }
+
+    /// The initial warehouse with the moves made so far, as a puzzle input the solver can replay.
+    pub fn to_puzzle_input(&self) -> String {
+        let mut initial = self
+            .history
+            .state_at(0)
+            .expect("the initial state is always known");
+        initial.instructions = self.history.directions().collect();
+        initial.to_string()
+    }
+
+    fn count(argument: Option<&str>) -> Result<usize, String> {
+        match argument {
+            Some(count) => count.parse().map_err(|_| "invalid count".to_string()),
+            None => Ok(1),
+        }
+    }
+
+    /// Executes a single command, returning the text to show.
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        let mut words = line.split_whitespace();
+        let argument = words.clone().nth(1);
+
+        match words.next() {
+            Some("u" | "undo") => {
+                let count = Self::count(argument)?;
+                for _ in 0..count {
+                    self.history.undo().ok_or("nothing to undo")?;
+                }
+                Ok(self.status())
+            }
+            Some("r" | "redo") => {
+                let count = Self::count(argument)?;
+                for _ in 0..count {
+                    self.history.redo().ok_or("nothing to redo")?;
+                }
+                Ok(self.status())
+            }
+            Some("m" | "moves") => Ok(self.moves()),
+            Some("save") => {
+                let path = argument.ok_or("missing file name")?;
+                std::fs::write(path, self.to_puzzle_input()).map_err(|e| e.to_string())?;
+                Ok(format!("saved {} moves to {path}", self.history.step()))
+            }
+            Some("h" | "help") => Ok(HELP.to_string()),
+            _ => {
+                let moves = parse_moves(line)?;
+                let blocked = moves
+                    .into_iter()
+                    .filter(|&direction| !self.history.play(direction).moved)
+                    .count();
+                match blocked {
+                    0 => Ok(self.status()),
+                    1 => Ok(format!("{}\n1 move was blocked", self.status())),
+                    n => Ok(format!("{}\n{n} moves were blocked", self.status())),
+                }
+            }
+        }
+    }
+
+    /// Reads commands until `quit` or the end of the input.
+    pub fn run_interactive(
+        &mut self,
+        input: impl BufRead,
+        mut output: impl Write,
+    ) -> std::io::Result<()> {
+        writeln!(output, "{}", self.status())?;
+        write!(output, "(play) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            if matches!(line.trim(), "q" | "quit") {
+                break;
+            }
+            match self.execute(&line) {
+                Ok(text) => writeln!(output, "{text}")?,
+                Err(error) => writeln!(output, "error: {error}")?,
+            }
+            write!(output, "(play) ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::puzzle_part1::Part1;
+
+    const WAREHOUSE: &str = concat!(
+        "#######\n", //
+        "#.....#\n", //
+        "#.@O..#\n", //
+        "#.....#\n", //
+        "#######\n", //
+        "\n",        //
+        "<"          //
+    );
+
+    fn game() -> Game {
+        Game::new(&PuzzleInput::try_from(WAREHOUSE).unwrap())
+    }
+
+    #[rstest]
+    #[case::typed("^v <>", Ok(vec![Direction::Up, Direction::Down, Direction::Left, Direction::Right]))]
+    #[case::arrows("\x1b[A\x1bOD\x1b[C", Ok(vec![Direction::Up, Direction::Left, Direction::Right]))]
+    #[case::empty("", Ok(vec![]))]
+    #[case::invalid("^x", Err("invalid move x".to_string()))]
+    #[case::unsupported_key("\x1b[5~", Err("unsupported key".to_string()))]
+    fn test_parse_moves(#[case] line: &str, #[case] expected: Result<Vec<Direction>, String>) {
+        assert_eq!(parse_moves(line), expected);
+    }
+
+    #[test]
+    fn test_play() {
+        let mut game = game();
+        assert_eq!(
+            game.execute(">>").unwrap(),
+            concat!(
+                "#######\n", //
+                "#.....#\n", //
+                "#...@O#\n", //
+                "#.....#\n", //
+                "#######\n", //
+                "moves: 2  GPS sum: 205"
+            )
+        );
+        assert!(game
+            .execute("\x1b[C")
+            .unwrap()
+            .ends_with("moves: 3  GPS sum: 205\n1 move was blocked"));
+        assert!(game
+            .execute("undo 2")
+            .unwrap()
+            .ends_with("moves: 1  GPS sum: 204"));
+        assert_eq!(game.execute("u 5"), Err("nothing to undo".to_string()));
+        assert_eq!(game.execute("moves").unwrap(), "");
+        assert!(game
+            .execute("redo")
+            .unwrap()
+            .ends_with("moves: 1  GPS sum: 204"));
+        assert_eq!(
+            game.execute("v<").unwrap().lines().nth(3).unwrap(),
+            "#.@...#"
+        );
+        assert_eq!(game.execute("m").unwrap(), ">v<");
+        assert_eq!(game.execute("jump"), Err("invalid move j".to_string()));
+    }
+
+    #[test]
+    /// The saved moves, replayed by the solver, end up with the same GPS sum.
+    fn test_replay() {
+        let mut game = game();
+        game.execute(">>>^<v<<<").unwrap();
+        let expected = game.history.state().coordinate_sum();
+
+        let saved = game.to_puzzle_input();
+        assert!(saved.ends_with("\n\n>>>^<v<<<"));
+        let replayed = PuzzleInput::try_from(saved.as_str()).unwrap();
+        assert_eq!(replayed.part1(), expected.to_string());
+    }
+
+    #[test]
+    fn test_interactive() {
+        let mut game = game();
+        let mut screen = Vec::new();
+        game.run_interactive(">\nquit\n>\n".as_bytes(), &mut screen)
+            .unwrap();
+        let screen = String::from_utf8(screen).unwrap();
+        assert!(screen.starts_with("#######\n"));
+        assert!(screen.contains("moves: 0  GPS sum: 203\n(play) "));
+        assert!(screen.ends_with("moves: 1  GPS sum: 204\n(play) "));
+    }
+}
@@ -9,14 +9,34 @@ pub enum GridElement {
     Empty,
     /// A wall, blocks movement.
     Wall,
-    // Movable object
+    /// A box (part 1), converted to an `Object` after initial parsing.
     Box,
-    /// The left part of a wide box (part 2)
+    /// The left part of a wide box (part 2), converted to an `Object` after initial parsing.
     BoxLeft,
-    /// The right part of a wide box (part 2)
+    /// The middle of a box that's wider than two cells, converted to an `Object` after initial
+    /// parsing.
+    BoxMiddle,
+    /// The right part of a wide box (part 2), converted to an `Object` after initial parsing.
     BoxRight,
-    /// Initial robot location, removed after initial parsing.
-    Robot,
+    /// A box that takes a stronger robot to push, converted to an `Object` after initial
+    /// parsing.
+    HeavyBox,
+    /// An immovable obstacle inside the warehouse. Blocks like a wall, but isn't part of the
+    /// outer walls.
+    Obstacle,
+    /// Initial robot location, with the robot's strength, removed after initial parsing.
+    Robot(usize),
+    /// A cell of a rigid, rectangular object that moves as a whole. `x` and `y` are the position
+    /// of the cell within the object. Only robots with a strength of at least `weight` can push
+    /// it.
+    Object {
+        id: usize,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        weight: usize,
+    },
 }
 
 impl From<char> for GridElement {
@@ -24,61 +44,209 @@ impl From<char> for GridElement {
         match c {
             '#' => GridElement::Wall,
             'O' => GridElement::Box,
+            'H' => GridElement::HeavyBox,
+            'X' => GridElement::Obstacle,
             '[' => GridElement::BoxLeft,
+            '=' => GridElement::BoxMiddle,
             ']' => GridElement::BoxRight,
             '.' => GridElement::Empty,
-            '@' => GridElement::Robot,
+            '@' => GridElement::Robot(1),
+            // A stronger robot, with its strength as the digit
+            '2'..='9' => GridElement::Robot(c.to_digit(10).unwrap() as usize),
             _ => panic!("Invalid character"),
         }
     }
 }
 
+/// Objects are drawn one row at a time, so an object that's more than one cell high looks the
+/// same as a stack of boxes. Only 1x1 objects show their weight.
 impl From<GridElement> for char {
     fn from(e: GridElement) -> Self {
         match e {
             GridElement::Wall => '#',
             GridElement::Box => 'O',
+            GridElement::HeavyBox => 'H',
+            GridElement::Obstacle => 'X',
             GridElement::Empty => '.',
-            GridElement::Robot => '@',
+            GridElement::Robot(strength) => Robot::symbol(strength),
             GridElement::BoxLeft => '[',
+            GridElement::BoxMiddle => '=',
             GridElement::BoxRight => ']',
+            GridElement::Object {
+                width: 1,
+                weight: 1,
+                ..
+            } => 'O',
+            GridElement::Object { width: 1, .. } => 'H',
+            GridElement::Object { x: 0, .. } => '[',
+            GridElement::Object { x, width, .. } if x == width - 1 => ']',
+            GridElement::Object { .. } => '=',
         }
     }
 }
 
+// Replaces the parsed boxes by objects: `O` is a 1x1 object, `H` a 1x1 object with weight 2, and
+// `[`, any number of `=` and `]` form a wide one. Unmatched box edges are left alone, for
+// `validate_assumptions` to find.
+fn identify_objects(grid: &mut Grid<GridElement>) {
+    let mut id = 0;
+    for y in 0..grid.height() {
+        let mut x = 0;
+        while x < grid.width() {
+            let weight = match grid[(x, y)] {
+                GridElement::HeavyBox => 2,
+                _ => 1,
+            };
+            let width = match grid[(x, y)] {
+                GridElement::Box | GridElement::HeavyBox => 1,
+                GridElement::BoxLeft => {
+                    let end = (x + 1..grid.width())
+                        .find(|&end| grid[(end, y)] != GridElement::BoxMiddle)
+                        .unwrap_or(grid.width() - 1);
+                    if grid[(end, y)] == GridElement::BoxRight {
+                        end - x + 1
+                    } else {
+                        0
+                    }
+                }
+                _ => 0,
+            };
+            for dx in 0..width {
+                grid[(x + dx, y)] = GridElement::Object {
+                    id,
+                    x: dx,
+                    y: 0,
+                    width,
+                    height: 1,
+                    weight,
+                };
+            }
+            if width > 0 {
+                id += 1;
+            }
+            x += width.max(1);
+        }
+    }
+}
+
+pub fn direction_char(direction: Direction) -> char {
+    match direction {
+        Direction::Up => '^',
+        Direction::Right => '>',
+        Direction::Down => 'v',
+        Direction::Left => '<',
+        _ => unreachable!(),
+    }
+}
+
+/// A robot, and how hard it can push.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Robot {
+    pub position: Coordinate,
+    /// The weight of the heaviest object it can push.
+    pub strength: usize,
+}
+
+impl Robot {
+    /// How a robot with `strength` is drawn: `@` for the default strength of 1, and the strength
+    /// as a digit for a stronger robot.
+    pub fn symbol(strength: usize) -> char {
+        match strength {
+            2..=9 => char::from_digit(strength as u32, 10).unwrap(),
+            _ => '@',
+        }
+    }
+}
+
+/// A single move of a robot, with enough information to undo it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Move {
+    pub direction: Direction,
+    /// The robot that moved, as an index in `PuzzleInput::robots`.
+    pub robot_index: usize,
+    /// The robot position before the move.
+    pub robot: Coordinate,
+    /// The cells that were shifted one step in `direction`, with what they contained.
+    pub displaced: Vec<(Coordinate, GridElement)>,
+    /// False if something blocked the move, and nothing changed.
+    pub moved: bool,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct PuzzleInput {
     pub grid: Grid<GridElement>,
-    pub robot: Coordinate,
+    pub robots: Vec<Robot>,
     pub instructions: VecDeque<Direction>,
+    /// The order in which the robots take turns following the instructions, repeated.
+    pub interleaving: Vec<usize>,
+    /// The number of turns taken so far.
+    pub turn: usize,
 }
 
 impl PuzzleInput {
     pub fn new(grid: Grid<GridElement>, instructions: Vec<Direction>) -> Self {
-        // Extract the robot position from the grid
-        let robot = grid
+        // Extract the robots from the grid, they take turns in reading order
+        let robots = grid
             .iter_pairs()
-            .find(|(_, c)| **c == GridElement::Robot)
-            .unwrap()
-            .0;
+            .filter_map(|(position, c)| match *c {
+                GridElement::Robot(strength) => Some(Robot { position, strength }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
         let mut grid = grid.clone();
 
-        // Make the grid position empty
-        grid[robot] = GridElement::Empty;
+        // Make the grid positions empty
+        for robot in &robots {
+            grid[robot.position] = GridElement::Empty;
+        }
+        identify_objects(&mut grid);
 
         PuzzleInput {
             grid,
-            robot,
+            interleaving: (0..robots.len()).collect(),
+            robots,
             instructions: instructions.into(),
+            turn: 0,
         }
     }
 
+    /// The position of the first robot. The puzzle itself only has the one.
+    pub fn robot(&self) -> Coordinate {
+        self.robots[0].position
+    }
+
     pub fn validate_assumptions(&self) -> Result<(), String> {
-        if self.grid.iter().any(|c| *c == GridElement::Robot) {
-            return Err("More than one Robot found".to_string());
+        if self.robots.is_empty() {
+            return Err("No robot found".to_string());
+        }
+
+        if self.interleaving.is_empty()
+            || self
+                .interleaving
+                .iter()
+                .any(|&robot| robot >= self.robots.len())
+        {
+            return Err("Invalid interleaving".to_string());
+        }
+
+        if self.grid.iter().any(|c| {
+            matches!(
+                c,
+                GridElement::Box
+                    | GridElement::HeavyBox
+                    | GridElement::BoxLeft
+                    | GridElement::BoxMiddle
+                    | GridElement::BoxRight
+            )
+        }) {
+            return Err("Unmatched box edge".to_string());
         }
 
-        if self.grid[self.robot] != GridElement::Empty {
+        if self
+            .robots
+            .iter()
+            .any(|robot| self.grid[robot.position] != GridElement::Empty)
+        {
             return Err("Robot is not on an empty space".to_string());
         }
 
@@ -102,18 +270,48 @@ impl PuzzleInput {
         Ok(())
     }
 
-    pub fn simulate_one_step(&mut self) {
+    pub fn simulate_one_step(&mut self) -> Move {
         // Find out the next direction to move
         let direction = self.instructions.pop_front().unwrap();
-        let mut coordinates_to_move = vec![self.robot + &direction];
+        self.take_turn(direction)
+    }
+
+    /// The robot whose turn it is.
+    pub fn next_robot(&self) -> usize {
+        self.interleaving[self.turn % self.interleaving.len()]
+    }
+
+    /// Moves the robot whose turn it is.
+    pub fn take_turn(&mut self, direction: Direction) -> Move {
+        let robot = self.next_robot();
+        self.turn += 1;
+        self.move_robot(robot, direction)
+    }
+
+    /// Moves a robot, pushing any boxes in the way, unless that would push something into a wall,
+    /// an obstacle or another robot, or something that's too heavy for the robot.
+    pub fn move_robot(&mut self, robot_index: usize, direction: Direction) -> Move {
+        let robot = self.robots[robot_index];
+        let blocked = Move {
+            direction,
+            robot_index,
+            robot: robot.position,
+            displaced: vec![],
+            moved: false,
+        };
+        let mut coordinates_to_move = vec![robot.position + &direction];
 
         let mut finger = 0usize;
 
         while finger < coordinates_to_move.len() {
             let next_position = coordinates_to_move[finger];
-            if self.grid[next_position] == GridElement::Wall {
-                // Wall cannot be moved
-                return;
+            if matches!(
+                self.grid[next_position],
+                GridElement::Wall | GridElement::Obstacle
+            ) || self.robots.iter().any(|r| r.position == next_position)
+            {
+                // Walls, obstacles and robots cannot be moved
+                return blocked;
             }
 
             let new_positions = self.expand_move(next_position, direction);
@@ -126,36 +324,65 @@ impl PuzzleInput {
             finger += 1;
         }
 
-        coordinates_to_move.iter().rev().for_each(|&c| {
+        let heaviest = coordinates_to_move
+            .iter()
+            .map(|&c| match self.grid[c] {
+                GridElement::Object { weight, .. } => weight,
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0);
+        if heaviest > robot.strength {
+            return blocked;
+        }
+
+        // Move the cells that are furthest along first, so each cell moves into an empty one
+        let step = Coordinate::from(direction);
+        coordinates_to_move.sort_by_key(|c| -c.dot(step));
+
+        let mut displaced = vec![];
+        coordinates_to_move.iter().for_each(|&c| {
             if self.grid[c] != GridElement::Empty {
                 assert!(self.grid[c + &direction] == GridElement::Empty);
+                displaced.push((c, self.grid[c]));
                 self.grid[c + &direction] = self.grid[c];
                 self.grid[c] = GridElement::Empty
             }
         });
-        self.robot += &direction;
+        self.robots[robot_index].position += &direction;
+
+        Move {
+            displaced,
+            moved: true,
+            ..blocked
+        }
     }
 
+    /// The cells that have to move along when the cell at `position` is pushed: for an object, the
+    /// whole object and everything in front of it.
     pub fn expand_move(&self, position: Coordinate, direction: Direction) -> Vec<Coordinate> {
         match self.grid[position] {
-            GridElement::Wall => vec![], // Wall cannot be moved
-            GridElement::Box => {
-                vec![position + &direction]
-            }
+            GridElement::Wall | GridElement::Obstacle => vec![], // Cannot be moved
             GridElement::Empty => vec![],
-            GridElement::Robot => unreachable!(),
-            GridElement::BoxLeft => match direction {
-                Direction::Up | Direction::Down => {
-                    vec![position + &Direction::Right, position + &direction]
-                }
-                _ => vec![position + &direction],
-            },
-            GridElement::BoxRight => match direction {
-                Direction::Up | Direction::Down => {
-                    vec![position + &Direction::Left, position + &direction]
-                }
-                _ => vec![position + &direction],
-            },
+            GridElement::Object {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => {
+                let origin = position - (x as isize, y as isize);
+                (0..height as isize)
+                    .flat_map(|dy| (0..width as isize).map(move |dx| origin + (dx, dy)))
+                    .flat_map(|c| [c, c + &direction])
+                    .collect()
+            }
+            GridElement::Box
+            | GridElement::HeavyBox
+            | GridElement::BoxLeft
+            | GridElement::BoxMiddle
+            | GridElement::BoxRight
+            | GridElement::Robot(_) => unreachable!("Removed after initial parsing"),
         }
     }
 
@@ -169,25 +396,59 @@ impl PuzzleInput {
         self.grid
             .iter_pairs()
             .map(|(c, e)| match e {
-                GridElement::Box | GridElement::BoxLeft => c.x() + 100 * c.y(),
+                // An object's GPS coordinate is that of its top left corner
+                GridElement::Object { x: 0, y: 0, .. } => c.x() + 100 * c.y(),
                 _ => 0,
             })
             .sum()
     }
 
+    /// The part 2 warehouse, where everything except the robot is twice as wide.
     pub fn expand(&self) -> PuzzleInput {
-        let expanded_grid = self.grid.iter().flat_map(|&c| match c {
-            GridElement::Wall => [GridElement::Wall, GridElement::Wall],
-            GridElement::Box => [GridElement::BoxLeft, GridElement::BoxRight],
-            GridElement::BoxLeft => unreachable!(),
-            GridElement::BoxRight => unreachable!(),
-            GridElement::Empty => [GridElement::Empty, GridElement::Empty],
-            GridElement::Robot => unreachable!(),
-        });
+        self.scale(2, 1)
+    }
+
+    /// Scales the warehouse: every cell becomes `horizontal` by `vertical` cells, and objects grow
+    /// along. Robots end up in the top left cell of their scaled cell.
+    pub fn scale(&self, horizontal: usize, vertical: usize) -> PuzzleInput {
+        let width = self.grid.width() * horizontal;
+        let height = self.grid.height() * vertical;
+        let scaled_grid = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| match self.grid[(x / horizontal, y / vertical)] {
+                GridElement::Object {
+                    id,
+                    x: object_x,
+                    y: object_y,
+                    width,
+                    height,
+                    weight,
+                } => GridElement::Object {
+                    id,
+                    x: object_x * horizontal + x % horizontal,
+                    y: object_y * vertical + y % vertical,
+                    width: width * horizontal,
+                    height: height * vertical,
+                    weight,
+                },
+                element => element,
+            });
         PuzzleInput {
-            grid: Grid::new_from_iter(self.grid.width() * 2, self.grid.height(), expanded_grid),
-            robot: Coordinate(self.robot.x() * 2, self.robot.y()),
+            grid: Grid::new_from_iter(width, height, scaled_grid),
+            robots: self
+                .robots
+                .iter()
+                .map(|robot| Robot {
+                    position: Coordinate(
+                        robot.position.x() * horizontal as isize,
+                        robot.position.y() * vertical as isize,
+                    ),
+                    ..*robot
+                })
+                .collect(),
             instructions: self.instructions.clone(),
+            interleaving: self.interleaving.clone(),
+            turn: self.turn,
         }
     }
 }
@@ -195,7 +456,9 @@ impl PuzzleInput {
 impl Display for PuzzleInput {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut formatted = self.grid.map(|c| char::from(*c));
-        formatted[self.robot] = '@';
+        for robot in &self.robots {
+            formatted[robot.position] = Robot::symbol(robot.strength);
+        }
         write!(f, "{}", formatted)?;
         writeln!(f)?;
         write!(
@@ -203,13 +466,7 @@ impl Display for PuzzleInput {
             "{}",
             self.instructions
                 .iter()
-                .map(|d| match d {
-                    Direction::Up => '^',
-                    Direction::Right => '>',
-                    Direction::Down => 'v',
-                    Direction::Left => '<',
-                    _ => unreachable!(),
-                })
+                .map(|&d| direction_char(d))
                 .collect::<String>()
         )?;
 
@@ -219,6 +476,8 @@ impl Display for PuzzleInput {
 
 #[cfg(test)]
 mod tests {
+    use rstest::rstest;
+
     use super::*;
 
     #[test]
@@ -375,4 +634,194 @@ mod tests {
             )
         );
     }
+
+    // The smaller example from the part 2 description
+    const SMALL_EXAMPLE: &str = concat!(
+        "#######\n",   //
+        "#...#.#\n",   //
+        "#.....#\n",   //
+        "#..OO@#\n",   //
+        "#..O..#\n",   //
+        "#.....#\n",   //
+        "#######\n",   //
+        "\n",          //
+        "<vv<<^^<<^^"  //
+    );
+
+    #[rstest]
+    #[case::small_example(SMALL_EXAMPLE, 1, 908)]
+    #[case::small_example_wide(SMALL_EXAMPLE, 2, 618)]
+    #[case::example_input2(include_str!("../example_input2.txt"), 1, 2028)]
+    #[case::example_input2_wide(include_str!("../example_input2.txt"), 2, 1751)]
+    /// Objects give the same answers as the separate box types they replaced.
+    fn test_scaled_answers(
+        #[case] input: &str,
+        #[case] horizontal: usize,
+        #[case] expected: isize,
+    ) {
+        let mut puzzle = PuzzleInput::try_from(input).unwrap().scale(horizontal, 1);
+        puzzle.simulate_all();
+        assert_eq!(puzzle.coordinate_sum(), expected);
+    }
+
+    #[test]
+    fn test_scale() {
+        let puzzle = PuzzleInput::try_from("####\n#O@#\n####\n\n<").unwrap();
+        assert_eq!(
+            puzzle.scale(3, 2).to_string(),
+            concat!(
+                "############\n", //
+                "############\n", //
+                "###[=]@..###\n", //
+                "###[=]...###\n", //
+                "############\n", //
+                "############\n", //
+                "\n",             //
+                "<"               //
+            )
+        );
+        assert_eq!(puzzle.expand(), puzzle.scale(2, 1));
+    }
+
+    #[test]
+    fn test_simulate_one_step_wider_box() {
+        let mut puzzle = PuzzleInput::try_from(concat!(
+            "#######\n", //
+            "#@[=].#\n", //
+            "#######\n", //
+            "\n",        //
+            ">>"         //
+        ))
+        .unwrap();
+
+        assert_eq!(puzzle.simulate_one_step().displaced.len(), 3);
+        assert!(!puzzle.simulate_one_step().moved);
+        assert_eq!(puzzle.to_string(), "#######\n#.@[=]#\n#######\n\n");
+        assert_eq!(puzzle.coordinate_sum(), 103);
+    }
+
+    #[test]
+    /// Pushing any cell of a tall object moves all of it.
+    fn test_simulate_one_step_tall_object() {
+        let mut puzzle = PuzzleInput::try_from(concat!(
+            "#####\n", //
+            "#...#\n", //
+            "#.O.#\n", //
+            "#.@.#\n", //
+            "#####\n", //
+            "\n",      //
+            ">^^^"     //
+        ))
+        .unwrap()
+        .scale(2, 2);
+        assert_eq!(puzzle.coordinate_sum(), 404);
+
+        puzzle.simulate_one_step();
+        assert_eq!(puzzle.simulate_one_step().displaced.len(), 4);
+        assert_eq!(puzzle.coordinate_sum(), 304);
+        puzzle.simulate_one_step();
+        assert_eq!(puzzle.coordinate_sum(), 204);
+        // The wall stops it
+        assert!(!puzzle.simulate_one_step().moved);
+        assert_eq!(puzzle.robot(), Coordinate(5, 4));
+    }
+
+    #[test]
+    fn test_unmatched_box_edge() {
+        assert_eq!(
+            PuzzleInput::try_from("#####\n#@[.#\n#####\n\n<"),
+            Err("Unmatched box edge".to_string())
+        );
+    }
+
+    #[test]
+    /// Robots take turns, and block each other.
+    fn test_multiple_robots() {
+        let mut puzzle = PuzzleInput::try_from(concat!(
+            "#######\n", //
+            "#.....#\n", //
+            "#@.O@.#\n", //
+            "#######\n", //
+            "\n",        //
+            "<<><"       //
+        ))
+        .unwrap();
+        assert_eq!(puzzle.robots.len(), 2);
+
+        assert!(!puzzle.simulate_one_step().moved);
+        assert_eq!(puzzle.simulate_one_step().robot_index, 1);
+        puzzle.simulate_all();
+        // Neither robot can push the box into the other one
+        assert_eq!(
+            puzzle.to_string(),
+            concat!(
+                "#######\n", //
+                "#.....#\n", //
+                "#@O@..#\n", //
+                "#######\n", //
+                "\n",        //
+            )
+        );
+        assert_eq!(puzzle.turn, 4);
+    }
+
+    #[test]
+    fn test_interleaving() {
+        let mut puzzle = PuzzleInput::try_from("#######\n#@...@#\n#######\n\n>><").unwrap();
+        puzzle.interleaving = vec![0, 0, 1];
+        puzzle.simulate_all();
+        assert_eq!(puzzle.robots[0].position, Coordinate(3, 1));
+        assert_eq!(puzzle.robots[1].position, Coordinate(4, 1));
+        assert_eq!(puzzle.next_robot(), 0);
+
+        puzzle.interleaving = vec![2];
+        assert_eq!(
+            puzzle.validate_assumptions(),
+            Err("Invalid interleaving".to_string())
+        );
+    }
+
+    #[test]
+    fn test_obstacle() {
+        let mut puzzle = PuzzleInput::try_from("######\n#@OX.#\n######\n\n>").unwrap();
+        assert!(!puzzle.simulate_one_step().moved);
+        assert_eq!(puzzle.to_string(), "######\n#@OX.#\n######\n\n");
+
+        // Obstacles don't count as walls
+        assert_eq!(
+            PuzzleInput::try_from("#####\n#@..X\n#####\n\n>"),
+            Err("Wall missing on the right edge".to_string())
+        );
+    }
+
+    #[test]
+    /// Heavy boxes only move for strong robots, even at the end of a row of boxes.
+    fn test_heavy_box() {
+        let mut puzzle = PuzzleInput::try_from("#######\n#@OH..#\n#######\n\n>>").unwrap();
+        assert!(!puzzle.simulate_one_step().moved);
+
+        puzzle.robots[0].strength = 2;
+        assert_eq!(puzzle.simulate_one_step().displaced.len(), 2);
+        // Robots stronger than the default are drawn as their strength
+        assert_eq!(puzzle.to_string(), "#######\n#.2OH.#\n#######\n\n");
+    }
+
+    #[test]
+    /// A digit is a robot with that strength.
+    fn test_strong_robot() {
+        let mut puzzle =
+            PuzzleInput::try_from("########\n#@OH...#\n#2OH...#\n########\n\n>>").unwrap();
+        assert_eq!(
+            puzzle.robots.iter().map(|r| r.strength).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        // The first robot is too weak, the second pushes both boxes
+        assert!(!puzzle.simulate_one_step().moved);
+        assert!(puzzle.simulate_one_step().moved);
+        assert_eq!(
+            puzzle.to_string(),
+            "########\n#@OH...#\n#.2OH..#\n########\n\n"
+        );
+    }
 }
@@ -6,7 +6,7 @@ use nom::{
     character::complete::{multispace0, newline, one_of},
     combinator::{map, opt},
     error::Error,
-    multi::{many1, separated_list1},
+    multi::{many0, many1, separated_list1},
     sequence::{delimited, separated_pair, terminated},
     Finish, IResult,
 };
@@ -28,11 +28,15 @@ fn parse_puzzle(input: &str) -> IResult<&str, PuzzleInput> {
     let puzzle_parser = map(
         separated_pair(
             map(
-                separated_list1(newline, many1(map(one_of("#@O[]."), GridElement::from))),
+                separated_list1(
+                    newline,
+                    many1(map(one_of("#@23456789OHX[=]."), GridElement::from)),
+                ),
                 Grid::from,
             ),
             many1(newline),
-            many1(map(terminated(one_of("^><v"), opt(newline)), |c| {
+            // No instructions is fine too, for the solver to find them
+            many0(map(terminated(one_of("^><v"), opt(newline)), |c| {
                 interpret_direction(c)
             })),
         ),
@@ -0,0 +1,522 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::Display;
+use std::rc::Rc;
+
+use aoc_grid::{grid_index::GridIndex, Coordinate, Direction};
+
+use crate::puzzle::{direction_char, GridElement, PuzzleInput};
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+/// What the solver is looking for.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Target {
+    /// Every object has its top left cell on one of these cells.
+    Goals(Vec<Coordinate>),
+    /// A GPS coordinate sum of at least this much.
+    CoordinateSum(isize),
+    /// The highest GPS coordinate sum that can be reached.
+    MaximumCoordinateSum,
+}
+
+/// Takes the goal cells out of a puzzle input, like in Sokoban: `G` is an empty goal cell, `*` a
+/// box on a goal and `+` a robot on a goal. Returns the input without them, which the parser
+/// reads, and the goals.
+pub fn split_goals(input: &str) -> (String, Vec<Coordinate>) {
+    let mut goals = vec![];
+    let mut in_map = true;
+    let mut output = String::with_capacity(input.len());
+    for (y, line) in input.split_inclusive('\n').enumerate() {
+        in_map &= !line.trim().is_empty();
+        for (x, c) in line.chars().enumerate() {
+            let replacement = match c {
+                'G' if in_map => '.',
+                '*' if in_map => 'O',
+                '+' if in_map => '@',
+                c => {
+                    output.push(c);
+                    continue;
+                }
+            };
+            goals.push(Coordinate(x as isize, y as isize));
+            output.push(replacement);
+        }
+    }
+    (output, goals)
+}
+
+/// The shortest sequence of moves found to reach a target.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Solution {
+    pub moves: Vec<Direction>,
+    /// The GPS coordinate sum after the moves.
+    pub coordinate_sum: isize,
+    /// The number of states the search expanded.
+    pub visited: usize,
+}
+
+/// Prints the moves in the puzzle's instruction format.
+impl Display for Solution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let moves = self.moves.iter().copied().map(direction_char);
+        write!(f, "{}", moves.collect::<String>())
+    }
+}
+
+// An object, by its top left cell, width, height and weight
+type ObjectKey = (Coordinate, usize, usize, usize);
+
+// What makes two states the same, packed small, as it's also all the search keeps of a state:
+// the cells of the robots, then the top left cells of the objects. Identical objects are
+// interchangeable, so objects are listed by shape, then by cell, rather than by id.
+#[derive(PartialEq, Eq, Hash)]
+struct StateKey {
+    // The position in the interleaving of the robot whose turn it is
+    phase: usize,
+    cells: Box<[u32]>,
+}
+
+fn objects(puzzle: &PuzzleInput) -> Vec<ObjectKey> {
+    puzzle
+        .grid
+        .iter_pairs()
+        .filter_map(|(c, e)| match *e {
+            GridElement::Object {
+                x: 0,
+                y: 0,
+                width,
+                height,
+                weight,
+                ..
+            } => Some((c, width, height, weight)),
+            _ => None,
+        })
+        .collect()
+}
+
+// What never changes between states: the warehouse without the objects, and the shapes of the
+// objects in the order `StateKey` lists them.
+struct Layout {
+    empty: PuzzleInput,
+    shapes: Vec<(usize, usize, usize)>,
+}
+
+impl Layout {
+    fn new(puzzle: &PuzzleInput) -> Self {
+        let mut empty = puzzle.clone();
+        let objects = objects(puzzle);
+        for &(origin, width, height, _) in &objects {
+            for y in 0..height as isize {
+                for x in 0..width as isize {
+                    empty.grid[origin + (x, y)] = GridElement::Empty;
+                }
+            }
+        }
+        let mut shapes = objects
+            .into_iter()
+            .map(|(_, width, height, weight)| (width, height, weight))
+            .collect::<Vec<_>>();
+        shapes.sort();
+        Layout { empty, shapes }
+    }
+
+    fn cell(&self, c: Coordinate) -> u32 {
+        (c.y() as usize * self.empty.grid.width() + c.x() as usize) as u32
+    }
+
+    fn coordinate(&self, cell: u32) -> Coordinate {
+        let width = self.empty.grid.width();
+        Coordinate(
+            (cell as usize % width) as isize,
+            (cell as usize / width) as isize,
+        )
+    }
+
+    fn key(&self, puzzle: &PuzzleInput) -> StateKey {
+        let mut objects = objects(puzzle)
+            .into_iter()
+            .map(|(origin, width, height, weight)| ((width, height, weight), self.cell(origin)))
+            .collect::<Vec<_>>();
+        objects.sort();
+        let robots = puzzle.robots.iter().map(|robot| self.cell(robot.position));
+        StateKey {
+            phase: puzzle.turn % puzzle.interleaving.len(),
+            cells: robots
+                .chain(objects.into_iter().map(|(_, cell)| cell))
+                .collect(),
+        }
+    }
+
+    // Object ids aren't part of the state, so they're handed out again.
+    fn restore(&self, key: &StateKey) -> PuzzleInput {
+        let mut puzzle = self.empty.clone();
+        let (robots, objects) = key.cells.split_at(puzzle.robots.len());
+        for (robot, &cell) in puzzle.robots.iter_mut().zip(robots) {
+            robot.position = self.coordinate(cell);
+        }
+        puzzle.turn = key.phase;
+        for (id, (&cell, &(width, height, weight))) in objects.iter().zip(&self.shapes).enumerate()
+        {
+            let origin = self.coordinate(cell);
+            for y in 0..height {
+                for x in 0..width {
+                    puzzle.grid[origin + (x as isize, y as isize)] = GridElement::Object {
+                        id,
+                        x,
+                        y,
+                        width,
+                        height,
+                        weight,
+                    };
+                }
+            }
+        }
+        puzzle
+    }
+
+    // Roughly what the search keeps in memory for a state: its node, its entry in the queue, and
+    // its key, which the node shares with the map of seen states.
+    fn state_size(&self) -> usize {
+        size_of::<Node>()
+            + size_of::<Reverse<(usize, usize, usize)>>()
+            + size_of::<(Rc<StateKey>, (usize, bool))>()
+            + size_of::<StateKey>()
+            + 2 * size_of::<usize>()
+            + size_of::<u32>() * (self.empty.robots.len() + self.shapes.len())
+    }
+}
+
+// True if no robot can ever move the object again, no matter where the other objects go. To move
+// it, there must be no wall or obstacle in front of it, and room for something to push it from
+// behind.
+fn is_frozen(puzzle: &PuzzleInput, (origin, width, height, weight): ObjectKey) -> bool {
+    let is_fixed =
+        |c: Coordinate| matches!(puzzle.grid[c], GridElement::Wall | GridElement::Obstacle);
+    let inside = |c: Coordinate| {
+        (origin.x()..origin.x() + width as isize).contains(&c.x())
+            && (origin.y()..origin.y() + height as isize).contains(&c.y())
+    };
+    if puzzle.robots.iter().all(|robot| robot.strength < weight) {
+        return true;
+    }
+
+    let cells = (0..height as isize)
+        .flat_map(|dy| (0..width as isize).map(move |dx| origin + (dx, dy)))
+        .collect::<Vec<_>>();
+    DIRECTIONS.iter().all(|direction| {
+        let mut behind = cells
+            .iter()
+            .map(|&c| c - Coordinate::from(*direction))
+            .filter(|&c| !inside(c));
+        let mut front = cells.iter().map(|&c| c + direction).filter(|&c| !inside(c));
+        front.any(is_fixed) || behind.all(is_fixed)
+    })
+}
+
+/// Searches for the shortest sequence of moves that reaches a target, with A*. The robots take
+/// turns as in the puzzle, so with more than one robot a move can also be a pass (a blocked
+/// move).
+#[derive(Debug, Clone)]
+pub struct Solver {
+    target: Target,
+    memory_limit: usize,
+}
+
+struct Node {
+    parent: Option<(usize, Direction)>,
+    state: Rc<StateKey>,
+}
+
+impl Solver {
+    pub fn new(target: Target) -> Self {
+        Solver {
+            target,
+            memory_limit: 1 << 30,
+        }
+    }
+
+    /// Gives up once the states it keeps, queued or expanded, take about this many bytes. The
+    /// default is 1 GiB.
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    fn is_reached(&self, puzzle: &PuzzleInput) -> bool {
+        match &self.target {
+            Target::Goals(goals) => objects(puzzle)
+                .iter()
+                .all(|(origin, ..)| goals.contains(origin)),
+            Target::CoordinateSum(sum) => puzzle.coordinate_sum() >= *sum,
+            Target::MaximumCoordinateSum => false,
+        }
+    }
+
+    // A lower bound on the number of moves left: every move pushes an object at most one cell. For
+    // the same reason, it drops by at most one per move, so it's consistent too.
+    fn heuristic(&self, puzzle: &PuzzleInput) -> usize {
+        match &self.target {
+            Target::Goals(goals) => objects(puzzle)
+                .iter()
+                .map(|(origin, ..)| {
+                    goals
+                        .iter()
+                        .map(|goal| origin.manhattan_distance(*goal))
+                        .min()
+                        .unwrap_or(usize::MAX)
+                })
+                .max()
+                .unwrap_or(0),
+            Target::CoordinateSum(_) | Target::MaximumCoordinateSum => 0,
+        }
+    }
+
+    // A state that can't reach the target: an object that can't move any more, off the goals. With
+    // no goals at all, no object can ever be on one.
+    fn is_deadlocked(&self, puzzle: &PuzzleInput) -> bool {
+        match &self.target {
+            Target::Goals(goals) => objects(puzzle).into_iter().any(|object| {
+                !goals.contains(&object.0) && (goals.is_empty() || is_frozen(puzzle, object))
+            }),
+            Target::CoordinateSum(_) | Target::MaximumCoordinateSum => false,
+        }
+    }
+
+    fn solution(
+        &self,
+        nodes: &[Node],
+        mut index: usize,
+        puzzle: &PuzzleInput,
+        visited: usize,
+    ) -> Solution {
+        let mut moves = vec![];
+        while let Some((parent, direction)) = nodes[index].parent {
+            moves.push(direction);
+            index = parent;
+        }
+        moves.reverse();
+        Solution {
+            moves,
+            coordinate_sum: puzzle.coordinate_sum(),
+            visited,
+        }
+    }
+
+    /// The shortest solution for the warehouse of `puzzle`, ignoring its instructions. Returns
+    /// None if the target can't be reached, or the state limit ran out first. For
+    /// `MaximumCoordinateSum`, this is the shortest way to the best sum seen before running out.
+    pub fn solve(&self, puzzle: &PuzzleInput) -> Option<Solution> {
+        let mut start = puzzle.clone();
+        start.instructions.clear();
+        if self.is_deadlocked(&start) {
+            return None;
+        }
+
+        // The fewest moves each state was queued with, and whether it was expanded. A state is
+        // closed when it's expanded, not when it's queued: a shorter way to it can turn up after it
+        // was first queued. With a consistent heuristic, the first time a state comes out of the
+        // queue, it's with the fewest moves. The nodes share their states with this map.
+        let layout = Layout::new(&start);
+        let state_limit = self.memory_limit / layout.state_size();
+        let start_key = Rc::new(layout.key(&start));
+        let mut seen = HashMap::from([(start_key.clone(), (0, false))]);
+        let mut queue = BinaryHeap::from([Reverse((self.heuristic(&start), 0, 0))]);
+        let mut nodes = vec![Node {
+            parent: None,
+            state: start_key,
+        }];
+        let mut best: Option<(isize, usize)> = None;
+        let mut visited = 0;
+
+        while let Some(Reverse((_, moves, index))) = queue.pop() {
+            let closed = &mut seen
+                .get_mut(&nodes[index].state)
+                .expect("queued states are seen")
+                .1;
+            if *closed {
+                continue;
+            }
+            *closed = true;
+            visited += 1;
+
+            let state = layout.restore(&nodes[index].state);
+            if self.is_reached(&state) {
+                return Some(self.solution(&nodes, index, &state, visited));
+            }
+            if best.is_none_or(|(sum, _)| state.coordinate_sum() > sum) {
+                best = Some((state.coordinate_sum(), index));
+            }
+            if nodes.len() >= state_limit {
+                break;
+            }
+
+            for direction in DIRECTIONS {
+                let mut next = state.clone();
+                next.take_turn(direction);
+                if self.is_deadlocked(&next) {
+                    continue;
+                }
+                let key = layout.key(&next);
+                if seen
+                    .get(&key)
+                    .is_some_and(|&(m, closed)| closed || m <= moves + 1)
+                {
+                    continue;
+                }
+                let key = Rc::new(key);
+                seen.insert(key.clone(), (moves + 1, false));
+                queue.push(Reverse((
+                    moves + 1 + self.heuristic(&next),
+                    moves + 1,
+                    nodes.len(),
+                )));
+                nodes.push(Node {
+                    parent: Some((index, direction)),
+                    state: key,
+                });
+            }
+        }
+
+        match (&self.target, best) {
+            (Target::MaximumCoordinateSum, Some((_, index))) => {
+                let mut state = puzzle.clone();
+                state.instructions.clear();
+                let mut solution = self.solution(&nodes, index, &state, visited);
+                solution.moves.iter().for_each(|&direction| {
+                    state.take_turn(direction);
+                });
+                solution.coordinate_sum = state.coordinate_sum();
+                Some(solution)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    // Replays a solution, returning the final state
+    fn replay(puzzle: &PuzzleInput, solution: &Solution) -> PuzzleInput {
+        let mut replayed = PuzzleInput::try_from(
+            format!(
+                "{}{solution}",
+                PuzzleInput {
+                    instructions: Default::default(),
+                    ..puzzle.clone()
+                }
+            )
+            .as_str(),
+        )
+        .unwrap();
+        replayed.simulate_all();
+        replayed
+    }
+
+    #[test]
+    fn test_split_goals() {
+        let (input, goals) = split_goals("#####\n#+*G#\n#####\n\n<");
+        assert_eq!(input, "#####\n#@O.#\n#####\n\n<");
+        assert_eq!(
+            goals,
+            vec![Coordinate(1, 1), Coordinate(2, 1), Coordinate(3, 1)]
+        );
+    }
+
+    #[rstest]
+    #[case::push_right("######\n#@O.G#\n######\n\n", ">>")]
+    #[case::around_the_box("#####\n#G..#\n#.O.#\n#..@#\n#####\n\n", "<^>^<")]
+    #[case::already_solved("#####\n#@*.#\n#####\n\n", "")]
+    #[case::wide_box("#######\n#.....#\n#@[]..#\n#..G..#\n#######\n\n", ">^>v")]
+    fn test_solve_goals(#[case] input: &str, #[case] expected: &str) {
+        let (input, goals) = split_goals(input);
+        let puzzle = PuzzleInput::try_from(input.as_str()).unwrap();
+        let solution = Solver::new(Target::Goals(goals.clone()))
+            .solve(&puzzle)
+            .unwrap();
+        assert_eq!(solution.to_string().len(), expected.len());
+
+        // Other solutions of the same length are just as good, so replay it instead
+        let replayed = replay(&puzzle, &solution);
+        assert!(objects(&replayed)
+            .iter()
+            .all(|(origin, ..)| goals.contains(origin)));
+    }
+
+    #[rstest]
+    // The box can't leave the wall, so it can't reach the goal below it
+    #[case::against_wall("######\n#.O.@#\n#...G#\n######\n\n")]
+    #[case::cornered("#####\n#O.G#\n#..@#\n#####\n\n")]
+    #[case::too_heavy("#####\n#@HG#\n#####\n\n")]
+    #[case::more_boxes_than_goals("######\n#@OOG#\n#....#\n######\n\n")]
+    #[case::no_goals("#####\n#@O.#\n#####\n\n")]
+    fn test_unsolvable(#[case] input: &str) {
+        let (input, goals) = split_goals(input);
+        let puzzle = PuzzleInput::try_from(input.as_str()).unwrap();
+        assert_eq!(Solver::new(Target::Goals(goals)).solve(&puzzle), None);
+    }
+
+    #[rstest]
+    #[case::corner("#####\n#O..#\n#..@#\n#####\n\n", true)]
+    #[case::against_wall("#####\n#.O.#\n#..@#\n#####\n\n", false)]
+    #[case::wide_box_in_corner("######\n#[]..#\n#...@#\n######\n\n", true)]
+    // The obstacle is only above half of the box, so it can be pushed down from the other half
+    #[case::partly_blocked("######\n#.X..#\n#.[].#\n#..@.#\n######\n\n", false)]
+    #[case::too_heavy("#####\n#.H.#\n#..@#\n#####\n\n", true)]
+    fn test_is_frozen(#[case] input: &str, #[case] expected: bool) {
+        let puzzle = PuzzleInput::try_from(input).unwrap();
+        assert_eq!(is_frozen(&puzzle, objects(&puzzle)[0]), expected);
+    }
+
+    #[test]
+    /// Without objects, there's nothing to put on the goals, so an empty goal list is reached.
+    fn test_no_goals_no_objects() {
+        let puzzle = PuzzleInput::try_from("####\n#@.#\n####\n\n").unwrap();
+        let solution = Solver::new(Target::Goals(vec![])).solve(&puzzle).unwrap();
+        assert!(solution.moves.is_empty());
+    }
+
+    #[test]
+    fn test_coordinate_sum() {
+        let puzzle = PuzzleInput::try_from("#####\n#@..#\n#.O.#\n#...#\n#####\n\n").unwrap();
+        assert_eq!(puzzle.coordinate_sum(), 202);
+
+        let solution = Solver::new(Target::CoordinateSum(203))
+            .solve(&puzzle)
+            .unwrap();
+        assert_eq!(solution.moves.len(), 2);
+        assert!(solution.coordinate_sum >= 203);
+
+        let best = Solver::new(Target::MaximumCoordinateSum)
+            .solve(&puzzle)
+            .unwrap();
+        assert_eq!(best.coordinate_sum, 303);
+        assert_eq!(replay(&puzzle, &best).coordinate_sum(), 303);
+        assert_eq!(best.moves.len(), 5);
+    }
+
+    #[test]
+    fn test_state_limit() {
+        let puzzle = PuzzleInput::try_from(include_str!("../example_input2.txt")).unwrap();
+        let solver = Solver::new(Target::CoordinateSum(isize::MAX)).with_memory_limit(10_000);
+        assert_eq!(solver.solve(&puzzle), None);
+    }
+
+    #[test]
+    /// With two robots, the second one has to wait for its turn.
+    fn test_two_robots() {
+        let (input, goals) = split_goals("#######\n#@.O.G#\n#@....#\n#######\n\n");
+        let puzzle = PuzzleInput::try_from(input.as_str()).unwrap();
+        let solution = Solver::new(Target::Goals(goals)).solve(&puzzle).unwrap();
+        assert_eq!(solution.moves.len(), 5);
+    }
+}
@@ -1,4 +1,5 @@
-use day17::puzzle::PuzzleInput;
+use day17::compiled::compile;
+use day17::puzzle::{PuzzleInput, VirtualMachine};
 use day17::puzzle_part1::Part1;
 use day17::puzzle_part2::Part2;
 
@@ -25,6 +26,31 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             group.bench_with_input(BenchmarkId::new("part1", name), &puzzle, |b, puzzle| {
                 b.iter(|| puzzle.part1());
             });
+            // The same run, interpreted and compiled. Compiling is included, as part 2 compiles
+            // once per search
+            let program = puzzle.get_program();
+            group.bench_with_input(
+                BenchmarkId::new("run_interpreted", name),
+                &program,
+                |b, program| {
+                    b.iter(|| VirtualMachine::new(puzzle.registers, program).count());
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new("run_compiled", name),
+                &program,
+                |b, program| {
+                    b.iter(|| compile(program).run(puzzle.registers).count());
+                },
+            );
+            let compiled = compile(&program);
+            group.bench_with_input(
+                BenchmarkId::new("run_precompiled", name),
+                &compiled,
+                |b, compiled| {
+                    b.iter(|| compiled.run(puzzle.registers).count());
+                },
+            );
             group.bench_with_input(BenchmarkId::new("part2", name), &puzzle, |b, puzzle| {
                 b.iter(|| puzzle.part2());
             });
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::{alpha1, alphanumeric1, char, digit1, space0, space1, u8},
+    combinator::{all_consuming, map, opt, recognize, rest},
+    multi::{many0_count, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    Finish, IResult,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+enum Statement<'a> {
+    Instruction {
+        mnemonic: &'a str,
+        operand: Option<&'a str>,
+    },
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Line<'a> {
+    label: Option<&'a str>,
+    statement: Option<Statement<'a>>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum OperandKind {
+    Combo,
+    Literal,
+    Jump,
+    // The operand is ignored, but it's still part of the bytecode
+    Ignored,
+}
+
+fn identifier(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        alt((alpha1, recognize(char('_')))),
+        many0_count(alt((alphanumeric1, recognize(char('_'))))),
+    ))(input)
+}
+
+fn parse_line(input: &str) -> IResult<&str, Line<'_>> {
+    let label = terminated(identifier, char(':'));
+    let bytes = map(
+        preceded(
+            pair(tag_no_case(".byte"), space1),
+            separated_list1(tuple((space0, char(','), space0)), u8),
+        ),
+        Statement::Bytes,
+    );
+    let instruction = map(
+        pair(alpha1, opt(preceded(space1, alt((identifier, digit1))))),
+        |(mnemonic, operand)| Statement::Instruction { mnemonic, operand },
+    );
+    let comment = preceded(char(';'), rest);
+
+    map(
+        all_consuming(tuple((
+            delimited(space0, opt(label), space0),
+            opt(alt((bytes, instruction))),
+            space0,
+            opt(comment),
+        ))),
+        |(label, statement, _, _)| Line { label, statement },
+    )(input)
+}
+
+fn instruction_info(mnemonic: &str) -> Option<(u8, OperandKind)> {
+    Some(match mnemonic.to_ascii_uppercase().as_str() {
+        "ADV" => (0, OperandKind::Combo),
+        "BXL" => (1, OperandKind::Literal),
+        "BST" => (2, OperandKind::Combo),
+        "JNZ" => (3, OperandKind::Jump),
+        "BXC" => (4, OperandKind::Ignored),
+        "OUT" => (5, OperandKind::Combo),
+        "BDV" => (6, OperandKind::Combo),
+        "CDV" => (7, OperandKind::Combo),
+        _ => return None,
+    })
+}
+
+fn parse_number(operand: &str) -> Result<u8, String> {
+    match operand.parse::<u8>() {
+        Ok(value) if value < 8 => Ok(value),
+        _ => Err(format!("invalid 3-bit operand {operand}")),
+    }
+}
+
+fn encode_operand(
+    kind: OperandKind,
+    operand: Option<&str>,
+    labels: &HashMap<&str, usize>,
+) -> Result<u8, String> {
+    match (kind, operand) {
+        (OperandKind::Ignored, None) => Ok(0),
+        (OperandKind::Ignored | OperandKind::Literal, Some(operand)) => parse_number(operand),
+        (OperandKind::Combo, Some(operand)) => match operand.to_ascii_uppercase().as_str() {
+            "A" => Ok(4),
+            "B" => Ok(5),
+            "C" => Ok(6),
+            _ => match parse_number(operand)? {
+                value @ 0..=3 => Ok(value),
+                value => Err(format!(
+                    "combo operand {value} is a register, use A, B or C instead"
+                )),
+            },
+        },
+        // The VM halts at an odd target, so reject those
+        (OperandKind::Jump, Some(operand)) => match labels.get(operand) {
+            Some(&address) if address >= 8 => Err(format!(
+                "label {operand} is at address {address}, which JNZ can't reach"
+            )),
+            Some(&address) if address % 2 == 1 => Err(format!(
+                "label {operand} is at odd address {address}, which JNZ can't reach"
+            )),
+            Some(&address) => Ok(address as u8),
+            None if operand.starts_with(|c: char| c.is_ascii_digit()) => {
+                match parse_number(operand)? {
+                    value if value % 2 == 1 => Err(format!("jump to odd address {value}")),
+                    value => Ok(value),
+                }
+            }
+            None => Err(format!("unknown label {operand}")),
+        },
+        (_, None) => Err("missing operand".to_string()),
+    }
+}
+
+/// Assembles source in the syntax of `OpCode`'s `Display` (and the disassembler listing) into
+/// bytecode.
+///
+/// Each line holds an optional `label:`, an optional instruction and an optional `; comment`.
+/// Jumps can use labels or numbers, and must go to an even address. `.byte 1,2,3` emits raw bytes.
+pub fn assemble(source: &str) -> Result<Vec<u8>, String> {
+    let lines = source
+        .lines()
+        .enumerate()
+        .map(|(number, line)| {
+            parse_line(line)
+                .finish()
+                .map(|(_, line)| line)
+                .map_err(|e| format!("line {}: syntax error near '{}'", number + 1, e.input))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // First pass: find the address of every label
+    let mut labels = HashMap::new();
+    let mut address = 0;
+    for (number, line) in lines.iter().enumerate() {
+        if let Some(label) = line.label {
+            if labels.insert(label, address).is_some() {
+                return Err(format!("line {}: duplicate label {label}", number + 1));
+            }
+        }
+        address += match &line.statement {
+            Some(Statement::Instruction { .. }) => 2,
+            Some(Statement::Bytes(bytes)) => bytes.len(),
+            None => 0,
+        };
+    }
+
+    // Second pass: encode the instructions
+    let mut bytecode = Vec::with_capacity(address);
+    for (number, line) in lines.iter().enumerate() {
+        match &line.statement {
+            Some(Statement::Instruction { mnemonic, operand }) => {
+                let (opcode, kind) = instruction_info(mnemonic)
+                    .ok_or_else(|| format!("line {}: unknown mnemonic {mnemonic}", number + 1))?;
+                let operand = encode_operand(kind, *operand, &labels)
+                    .map_err(|e| format!("line {}: {e}", number + 1))?;
+                bytecode.extend([opcode, operand]);
+            }
+            Some(Statement::Bytes(bytes)) => {
+                if let Some(byte) = bytes.iter().find(|&&byte| byte > 7) {
+                    return Err(format!("line {}: byte {byte} is not 3-bit", number + 1));
+                }
+                bytecode.extend(bytes);
+            }
+            None => {}
+        }
+    }
+
+    Ok(bytecode)
+}
+
+/// Formats bytecode the way the puzzle input does.
+pub fn program_line(bytecode: &[u8]) -> String {
+    let values = bytecode.iter().map(|b| b.to_string()).collect::<Vec<_>>();
+    format!("Program: {}", values.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::disassembler::disassemble;
+    use crate::puzzle::{PuzzleInput, VirtualMachine};
+
+    #[test]
+    fn test_assemble() {
+        let source = "
+            ; The example program
+            start:  ADV 1   ; A = A >> 1
+                    OUT A
+                    jnz start
+        ";
+        let bytecode = assemble(source).unwrap();
+        assert_eq!(bytecode, vec![0, 1, 5, 4, 3, 0]);
+        assert_eq!(program_line(&bytecode), "Program: 0,1,5,4,3,0");
+    }
+
+    #[rstest]
+    #[case::example(&[0, 1, 5, 4, 3, 0])]
+    #[case::final_program(&[2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0])]
+    #[case::forward_jump(&[3, 4, 5, 4, 5, 5])]
+    #[case::invalid(&[5, 7, 3, 1, 4])]
+    /// Disassembling and re-assembling must produce the same bytecode.
+    fn test_round_trip(#[case] bytecode: &[u8]) {
+        let listing = disassemble(bytecode).to_string();
+        assert_eq!(assemble(&listing).unwrap(), bytecode);
+    }
+
+    #[rstest]
+    #[case::unknown_mnemonic("FOO 1", "line 1: unknown mnemonic FOO")]
+    #[case::unknown_label("JNZ nowhere", "line 1: unknown label nowhere")]
+    #[case::duplicate_label("a:\na: OUT A", "line 2: duplicate label a")]
+    #[case::missing_operand("OUT", "line 1: missing operand")]
+    #[case::register_number(
+        "OUT 5",
+        "line 1: combo operand 5 is a register, use A, B or C instead"
+    )]
+    #[case::not_3_bit("BXL 8", "line 1: invalid 3-bit operand 8")]
+    #[case::bad_byte(".byte 1, 9", "line 1: byte 9 is not 3-bit")]
+    #[case::syntax("OUT A B", "line 1: syntax error near 'B'")]
+    #[case::odd_jump("JNZ 3", "line 1: jump to odd address 3")]
+    #[case::odd_label(
+        ".byte 1\nodd: OUT A\nJNZ odd",
+        "line 3: label odd is at odd address 1, which JNZ can't reach"
+    )]
+    #[case::unreachable_label(
+        "OUT A\nOUT A\nOUT A\nOUT A\nfar: OUT A\nJNZ far",
+        "line 6: label far is at address 8, which JNZ can't reach"
+    )]
+    fn test_assemble_errors(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(assemble(source), Err(expected.to_string()));
+    }
+
+    #[rstest]
+    #[case::underscore("my_loop: OUT A\nJNZ my_loop", vec![5, 4, 3, 0])]
+    #[case::leading_underscore("OUT A\n_x1: ADV 1\nJNZ _x1", vec![5, 4, 0, 1, 3, 2])]
+    fn test_assemble_labels(#[case] source: &str, #[case] expected: Vec<u8>) {
+        assert_eq!(assemble(source), Ok(expected));
+    }
+
+    #[test]
+    /// Programs with a jump to a label that isn't at the start must run correctly on the VM.
+    fn test_run_assembled() {
+        let source = "
+                    BST A
+            loop:   OUT B
+                    BXL 1
+                    ADV 1
+                    JNZ loop
+        ";
+        let bytecode = assemble(source).unwrap();
+        let input = PuzzleInput::try_from(
+            format!(
+                "Register A: 7\nRegister B: 0\nRegister C: 0\n\n{}",
+                program_line(&bytecode)
+            )
+            .as_str(),
+        )
+        .unwrap();
+        let program = input.get_program();
+        let output = VirtualMachine::new(input.registers, &program).collect::<Vec<_>>();
+        assert_eq!(output, vec![7, 6, 7]);
+    }
+}
@@ -0,0 +1,315 @@
+use aoc_vm::Word;
+
+use crate::puzzle::{OpCode, Operand};
+
+// A straight-line instruction, with its operands already resolved. Returns the output, if any.
+type Operation<W> = Box<dyn Fn(&mut [W; 3]) -> Option<u8>>;
+
+// How a basic block ends
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Exit {
+    /// Continue with the next block.
+    FallThrough,
+    /// JNZ: jump to a block if A is non-zero, otherwise fall through. None jumps past the end of
+    /// the program, or to an odd address, halting it.
+    Jnz(Option<usize>),
+}
+
+struct Block<W> {
+    operations: Vec<Operation<W>>,
+    exit: Exit,
+}
+
+/// A program compiled into basic blocks of closures, so running it doesn't match on opcodes and
+/// operands for every instruction. Runs exactly like the `VirtualMachine`, including the cycle
+/// count.
+pub struct CompiledProgram<W = i64> {
+    blocks: Vec<Block<W>>,
+}
+
+enum Source<W> {
+    Literal(W),
+    Register(usize),
+}
+
+fn source<W: Word>(operand: &Operand) -> Source<W> {
+    match operand {
+        Operand::Literal(value) => Source::Literal(W::from_u8(*value)),
+        Operand::RegisterA => Source::Register(0),
+        Operand::RegisterB => Source::Register(1),
+        Operand::RegisterC => Source::Register(2),
+    }
+}
+
+// ADV, BDV and CDV: shift A right into a register
+fn shift<W: Word + 'static>(target: usize, operand: &Operand) -> Operation<W> {
+    match source(operand) {
+        Source::Literal(amount) => Box::new(move |r| {
+            r[target] = r[0].shift_right(amount);
+            None
+        }),
+        Source::Register(amount) => Box::new(move |r| {
+            r[target] = r[0].shift_right(r[amount]);
+            None
+        }),
+    }
+}
+
+fn compile_operation<W: Word + 'static>(opcode: &OpCode) -> Operation<W> {
+    match opcode {
+        OpCode::Adv(operand) => shift(0, operand),
+        OpCode::Bdv(operand) => shift(1, operand),
+        OpCode::Cdv(operand) => shift(2, operand),
+        OpCode::Bxl(value) => {
+            let value = W::from_u8(*value);
+            Box::new(move |r| {
+                r[1] = r[1] ^ value;
+                None
+            })
+        }
+        OpCode::Bst(operand) => match source(operand) {
+            // Literals are at most 3, so they're already 3-bit
+            Source::Literal(value) => Box::new(move |r| {
+                r[1] = value;
+                None
+            }),
+            Source::Register(index) => Box::new(move |r| {
+                r[1] = W::from_u8(r[index].low_bits(3));
+                None
+            }),
+        },
+        OpCode::Bxc => Box::new(|r| {
+            r[1] = r[1] ^ r[2];
+            None
+        }),
+        OpCode::Out(operand) => match source::<W>(operand) {
+            Source::Literal(value) => {
+                let value = value.low_bits(3);
+                Box::new(move |_| Some(value))
+            }
+            Source::Register(index) => Box::new(move |r| Some(r[index].low_bits(3))),
+        },
+        OpCode::Jnz(_) => unreachable!("jumps end a basic block"),
+    }
+}
+
+// The instruction index a JNZ jumps to. The target is a byte address, and the interpreter halts at
+// an odd one, so that has no index.
+fn jump_target(value: u8) -> Option<usize> {
+    value.is_multiple_of(2).then_some(value as usize / 2)
+}
+
+/// Compiles a program. Blocks start at the start of the program, at every jump target, and after
+/// every jump.
+pub fn compile<W: Word + 'static>(program: &[OpCode]) -> CompiledProgram<W> {
+    let mut leaders = vec![false; program.len() + 1];
+    leaders[0] = true;
+    for (pc, opcode) in program.iter().enumerate() {
+        if let OpCode::Jnz(value) = opcode {
+            leaders[pc + 1] = true;
+            if let Some(leader) = jump_target(*value).and_then(|target| leaders.get_mut(target)) {
+                *leader = true;
+            }
+        }
+    }
+
+    // The block that starts at each leader
+    let mut block_index = vec![None; program.len() + 1];
+    let starts = (0..program.len())
+        .filter(|&pc| leaders[pc])
+        .collect::<Vec<_>>();
+    for (index, &start) in starts.iter().enumerate() {
+        block_index[start] = Some(index);
+    }
+
+    let blocks = starts
+        .iter()
+        .enumerate()
+        .map(|(index, &start)| {
+            let end = starts.get(index + 1).copied().unwrap_or(program.len());
+            let (body, exit) = match &program[start..end] {
+                [body @ .., OpCode::Jnz(value)] => (
+                    body,
+                    Exit::Jnz(
+                        jump_target(*value)
+                            .and_then(|target| block_index.get(target).copied().flatten()),
+                    ),
+                ),
+                body => (body, Exit::FallThrough),
+            };
+            Block {
+                operations: body.iter().map(compile_operation).collect(),
+                exit,
+            }
+        })
+        .collect();
+
+    CompiledProgram { blocks }
+}
+
+impl<W: Word + 'static> CompiledProgram<W> {
+    pub fn run(&self, registers: [W; 3]) -> CompiledRun<'_, W> {
+        CompiledRun {
+            program: self,
+            registers,
+            block: 0,
+            index: 0,
+            cycles: 0,
+            instruction_limit: None,
+        }
+    }
+}
+
+/// A run of a `CompiledProgram`. As an iterator, it yields the output.
+pub struct CompiledRun<'a, W> {
+    program: &'a CompiledProgram<W>,
+    pub registers: [W; 3],
+    block: usize,
+    // The next operation in the block, or the exit when past the operations
+    index: usize,
+    // The number of instructions executed so far
+    pub cycles: usize,
+    instruction_limit: Option<usize>,
+}
+
+impl<W: Word> CompiledRun<'_, W> {
+    /// Stops the iterator after this many instructions, for programs that may not terminate.
+    pub fn with_instruction_limit(mut self, limit: usize) -> Self {
+        self.instruction_limit = Some(limit);
+        self
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.block >= self.program.blocks.len()
+    }
+
+    /// True if the iterator stopped because of the instruction limit, rather than halting.
+    pub fn limit_reached(&self) -> bool {
+        !self.is_halted()
+            && self
+                .instruction_limit
+                .is_some_and(|limit| self.cycles >= limit)
+    }
+}
+
+impl<W: Word> Iterator for CompiledRun<'_, W> {
+    type Item = u8;
+    fn next(&mut self) -> Option<u8> {
+        while let Some(block) = self.program.blocks.get(self.block) {
+            if let Some(operation) = block.operations.get(self.index) {
+                if self.limit_reached() {
+                    return None;
+                }
+                self.index += 1;
+                self.cycles += 1;
+                if let Some(output) = operation(&mut self.registers) {
+                    return Some(output);
+                }
+                continue;
+            }
+
+            self.block = match block.exit {
+                Exit::FallThrough => self.block + 1,
+                Exit::Jnz(target) => {
+                    if self.limit_reached() {
+                        return None;
+                    }
+                    self.cycles += 1;
+                    if self.registers[0] != W::ZERO {
+                        target.unwrap_or(self.program.blocks.len())
+                    } else {
+                        self.block + 1
+                    }
+                }
+            };
+            self.index = 0;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::assembler::assemble;
+    use crate::puzzle::{PuzzleInput, VirtualMachine};
+
+    fn program(bytecode: &[u8]) -> Vec<OpCode> {
+        PuzzleInput {
+            registers: [0, 0, 0],
+            bytecode: bytecode.to_vec(),
+        }
+        .get_program()
+    }
+
+    // Runs both ways, checking that the output, registers and cycles match
+    fn assert_same_as_interpreter(program: &[OpCode], registers: [i64; 3]) -> Vec<u8> {
+        let mut vm = VirtualMachine::new(registers, program);
+        let expected = vm.by_ref().collect::<Vec<_>>();
+
+        let compiled = compile(program);
+        let mut run = compiled.run(registers);
+        assert_eq!(run.by_ref().collect::<Vec<_>>(), expected);
+        assert_eq!(run.registers, vm.registers);
+        assert_eq!(run.cycles, vm.cycles);
+        assert!(run.is_halted());
+        expected
+    }
+
+    #[rstest]
+    #[case::example(include_str!("../example_input.txt"))]
+    #[case::final_input(include_str!("../input.txt"))]
+    fn test_puzzle_input(#[case] input: &str) {
+        let input = PuzzleInput::try_from(input).unwrap();
+        assert_same_as_interpreter(&input.get_program(), input.registers);
+    }
+
+    #[rstest]
+    #[case::final_program(&[2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0], [164516454365621, 0, 0])]
+    #[case::literals(&[2, 3, 1, 7, 5, 5, 5, 2, 0, 2, 3, 0], [1000, 0, 0])]
+    #[case::large_shift(&[0, 5, 6, 6, 5, 4, 5, 6], [-5, 70, 0])]
+    #[case::jump_past_end(&[0, 1, 3, 6, 5, 4], [4, 0, 0])]
+    #[case::misaligned_jump(&[0, 1, 5, 4, 3, 1], [16, 0, 0])]
+    #[case::empty(&[], [1, 2, 3])]
+    fn test_same_as_interpreter(#[case] bytecode: &[u8], #[case] registers: [i64; 3]) {
+        assert_same_as_interpreter(&program(bytecode), registers);
+    }
+
+    #[test]
+    fn test_jump_into_block() {
+        let bytecode = assemble(
+            "
+                    BST A
+            loop:   OUT B
+                    BXL 1
+                    ADV 1
+                    JNZ loop
+                    OUT A
+            ",
+        )
+        .unwrap();
+        let output = assert_same_as_interpreter(&program(&bytecode), [7, 0, 0]);
+        assert_eq!(output, vec![7, 6, 7, 0]);
+    }
+
+    #[test]
+    fn test_wide_registers() {
+        let program = program(&[2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0]);
+        let a = 164516454365621u128 | 1 << 120;
+        let expected = VirtualMachine::<u128>::new([a, 0, 0], &program).collect::<Vec<_>>();
+        let compiled = compile::<u128>(&program);
+        assert_eq!(compiled.run([a, 0, 0]).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_instruction_limit() {
+        // BXL 1 / JNZ 0 never terminates while A is non-zero
+        let compiled = compile(&[OpCode::Bxl(1), OpCode::Jnz(0)]);
+        let mut run = compiled.run([1, 0, 0]).with_instruction_limit(100);
+        assert_eq!(run.next(), None);
+        assert!(run.limit_reached());
+        assert_eq!(run.cycles, 100);
+    }
+}
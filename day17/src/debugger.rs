@@ -0,0 +1,111 @@
+use crate::disassembler::disassemble;
+use crate::puzzle::{OpCode, ThreeBitComputer};
+
+/// The `aoc_vm` step debugger, for the 3-bit computer. Addresses are byte addresses, like in the
+/// disassembler listing.
+pub type Debugger<'a, W = i64> = aoc_vm::Debugger<'a, ThreeBitComputer<W>, 3>;
+
+/// A debugger for `program`, with the labels of the disassembly of `bytecode` in its listing.
+pub fn debugger<'a>(registers: [i64; 3], program: &'a [OpCode], bytecode: &[u8]) -> Debugger<'a> {
+    let disassembly = disassemble(bytecode);
+    let labels = disassembly
+        .instructions
+        .iter()
+        .filter_map(|instruction| {
+            let label = disassembly.label(instruction.address)?;
+            Some((instruction.address, label))
+        })
+        .collect::<Vec<_>>();
+    Debugger::new(registers, program).with_labels(labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::PuzzleInput;
+
+    fn example() -> PuzzleInput {
+        PuzzleInput::try_from(include_str!("../example_input.txt")).unwrap()
+    }
+
+    #[test]
+    fn test_step() {
+        let input = example();
+        let program = input.get_program();
+        let mut debugger = debugger(input.registers, &program, &input.bytecode);
+
+        assert_eq!(
+            debugger.execute("step").unwrap(),
+            "02: OUT A\nA=364 B=0 C=0"
+        );
+        assert_eq!(debugger.execute("s 2").unwrap(), "00: ADV 1\nA=364 B=0 C=0");
+        assert_eq!(debugger.execute("output").unwrap(), "4");
+    }
+
+    #[test]
+    fn test_breakpoint() {
+        let input = example();
+        let program = input.get_program();
+        let mut debugger = debugger(input.registers, &program, &input.bytecode);
+
+        debugger.execute("break 4").unwrap();
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "breakpoint at 04: JNZ 0\nA=364 B=0 C=0"
+        );
+        // Continuing from a breakpoint doesn't stop at the same breakpoint immediately
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "breakpoint at 04: JNZ 0\nA=182 B=0 C=0"
+        );
+        debugger.execute("delete 4").unwrap();
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "program halted\nA=0 B=0 C=0"
+        );
+        assert_eq!(debugger.execute("o").unwrap(), "4,6,3,5,6,3,5,2,1,0");
+        assert!(debugger.execute("break 3").is_err());
+    }
+
+    #[test]
+    fn test_watchpoint() {
+        let input = PuzzleInput {
+            registers: [23999685, 0, 0],
+            bytecode: vec![2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0],
+        };
+        let program = input.get_program();
+        let mut debugger = debugger(input.registers, &program, &input.bytecode);
+
+        debugger.execute("watch a").unwrap();
+        debugger.execute("radix oct").unwrap();
+        assert_eq!(
+            debugger.execute("continue").unwrap(),
+            "watchpoint: A changed from 0o133432305 to 0o13343230, now at 10: BXC\nA=0o13343230 B=0o1 C=0o5561514"
+        );
+    }
+
+    #[test]
+    fn test_interactive() {
+        let input = example();
+        let program = input.get_program();
+        let mut debugger = debugger(input.registers, &program, &input.bytecode);
+
+        let mut screen = Vec::new();
+        debugger
+            .run_interactive("list\n\nfoo\nquit\nstep\n".as_bytes(), &mut screen)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(screen).unwrap(),
+            "00: ADV 1
+A=729 B=0 C=0
+(debug)  L00:
+>   00: ADV 1
+    02: OUT A
+    04: JNZ 0
+(debug) 02: OUT A
+A=364 B=0 C=0
+(debug) error: unknown command foo, try 'help'
+(debug) "
+        );
+    }
+}
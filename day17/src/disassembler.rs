@@ -0,0 +1,273 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use crate::puzzle::{OpCode, Operand};
+
+/// A single decoded instruction, at a byte address in the bytecode.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Instruction {
+    pub address: usize,
+    pub bytes: [u8; 2],
+    // None when the operand is the reserved combo operand 7
+    pub opcode: Option<OpCode>,
+}
+
+/// Things in the bytecode that the VM wouldn't handle the way the puzzle describes.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Warning {
+    /// A jump to an odd address, which would execute an operand as an opcode.
+    MisalignedJump { address: usize, target: u8 },
+    /// A combo operand of 7, which the puzzle says doesn't appear in valid programs.
+    ReservedOperand { address: usize },
+    /// The bytecode has an odd length, so the last byte has no operand.
+    TrailingByte { address: usize },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::MisalignedJump { address, target } => {
+                write!(f, "{address:02}: jump to misaligned address {target}")
+            }
+            Warning::ReservedOperand { address } => {
+                write!(f, "{address:02}: reserved combo operand 7")
+            }
+            Warning::TrailingByte { address } => write!(f, "{address:02}: trailing byte"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Disassembly {
+    pub instructions: Vec<Instruction>,
+    pub trailing_byte: Option<u8>,
+    pub warnings: Vec<Warning>,
+    // Addresses that are the target of a jump
+    jump_targets: BTreeSet<usize>,
+}
+
+fn uses_combo_operand(opcode: u8) -> bool {
+    matches!(opcode, 0 | 2 | 5 | 6 | 7)
+}
+
+/// Decodes bytecode into instructions, collecting jump targets and anything suspicious.
+pub fn disassemble(bytecode: &[u8]) -> Disassembly {
+    let mut warnings = Vec::new();
+    let mut jump_targets = BTreeSet::new();
+
+    let instructions = bytecode
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(index, pair)| {
+            let address = index * 2;
+            let bytes = [pair[0], pair[1]];
+            let opcode = if uses_combo_operand(bytes[0]) && bytes[1] == 7 {
+                warnings.push(Warning::ReservedOperand { address });
+                None
+            } else {
+                Some(OpCode::from(bytes))
+            };
+
+            if let Some(OpCode::Jnz(target)) = opcode {
+                if !target.is_multiple_of(2) {
+                    warnings.push(Warning::MisalignedJump { address, target });
+                } else if (target as usize) < bytecode.len() {
+                    jump_targets.insert(target as usize);
+                }
+            }
+
+            Instruction {
+                address,
+                bytes,
+                opcode,
+            }
+        })
+        .collect();
+
+    let trailing_byte = if !bytecode.len().is_multiple_of(2) {
+        warnings.push(Warning::TrailingByte {
+            address: bytecode.len() - 1,
+        });
+        bytecode.last().copied()
+    } else {
+        None
+    };
+
+    Disassembly {
+        instructions,
+        trailing_byte,
+        warnings,
+        jump_targets,
+    }
+}
+
+impl Disassembly {
+    pub fn label(&self, address: usize) -> Option<String> {
+        self.jump_targets
+            .contains(&address)
+            .then(|| format!("L{address:02}"))
+    }
+
+    // The mnemonic, with jump targets replaced by their label
+    fn mnemonic(&self, opcode: &OpCode) -> String {
+        match opcode {
+            OpCode::Jnz(target) => match self.label(*target as usize) {
+                Some(label) => format!("JNZ {label}"),
+                None => opcode.to_string(),
+            },
+            _ => opcode.to_string(),
+        }
+    }
+
+    // BXC ignores its operand, but the listing keeps it so the bytecode can be reassembled. The
+    // assembler rejects misaligned jumps, so those are listed as bytes.
+    fn instruction_text(&self, instruction: &Instruction) -> String {
+        let [opcode, operand] = instruction.bytes;
+        match &instruction.opcode {
+            Some(OpCode::Bxc) if operand != 0 => format!("BXC {operand}"),
+            Some(OpCode::Jnz(target)) if !target.is_multiple_of(2) => {
+                format!(".byte {opcode},{operand}")
+            }
+            Some(decoded) => self.mnemonic(decoded),
+            None => format!(".byte {opcode},{operand}"),
+        }
+    }
+
+    fn jump_description(&self, target: u8) -> String {
+        match self.label(target as usize) {
+            Some(label) => format!("goto {label}"),
+            None if !target.is_multiple_of(2) => format!("goto {target} (misaligned)"),
+            None => "halt".to_string(),
+        }
+    }
+
+    /// A readable, C-like rendering of the program.
+    pub fn pseudo_code(&self) -> String {
+        let mut lines = Vec::new();
+        for instruction in &self.instructions {
+            if let Some(label) = self.label(instruction.address) {
+                lines.push(format!("{label}:"));
+            }
+            let statement = match &instruction.opcode {
+                Some(opcode) => pseudo_statement(opcode, |target| self.jump_description(target)),
+                None => format!("invalid {},{}", instruction.bytes[0], instruction.bytes[1]),
+            };
+            lines.push(format!("    {statement}"));
+        }
+        if let Some(byte) = self.trailing_byte {
+            lines.push(format!("    invalid {byte}"));
+        }
+        lines.join("\n")
+    }
+}
+
+fn pseudo_statement(opcode: &OpCode, jump: impl Fn(u8) -> String) -> String {
+    match opcode {
+        OpCode::Adv(operand) => format!("A = A >> {operand}"),
+        OpCode::Bdv(operand) => format!("B = A >> {operand}"),
+        OpCode::Cdv(operand) => format!("C = A >> {operand}"),
+        OpCode::Bxl(value) => format!("B = B ^ {value}"),
+        OpCode::Bst(Operand::Literal(value)) => format!("B = {value}"),
+        OpCode::Bst(operand) => format!("B = {operand} % 8"),
+        OpCode::Jnz(target) => format!("if A != 0 {}", jump(*target)),
+        OpCode::Bxc => "B = B ^ C".to_string(),
+        OpCode::Out(operand) => format!("out({operand} % 8)"),
+    }
+}
+
+/// The listing is valid input for the assembler: addresses and bytecode are in comments, and
+/// anything that doesn't decode is emitted as raw bytes.
+impl Display for Disassembly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for instruction in &self.instructions {
+            if let Some(label) = self.label(instruction.address) {
+                writeln!(f, "{label}:")?;
+            }
+            let [opcode, operand] = instruction.bytes;
+            let text = self.instruction_text(instruction);
+            writeln!(
+                f,
+                "    {text:<10} ; {:02}: {opcode},{operand}",
+                instruction.address
+            )?;
+        }
+        if let Some(byte) = self.trailing_byte {
+            let address = self.instructions.len() * 2;
+            let text = format!(".byte {byte}");
+            writeln!(f, "    {text:<10} ; {address:02}: {byte}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    const FINAL_PROGRAM: [u8; 16] = [2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0];
+
+    #[test]
+    fn test_listing() {
+        let disassembly = disassemble(&FINAL_PROGRAM);
+        assert!(disassembly.warnings.is_empty());
+        assert_eq!(
+            disassembly.to_string(),
+            "L00:
+    BST A      ; 00: 2,4
+    BXL 1      ; 02: 1,1
+    CDV B      ; 04: 7,5
+    BXL 5      ; 06: 1,5
+    ADV 3      ; 08: 0,3
+    BXC 4      ; 10: 4,4
+    OUT B      ; 12: 5,5
+    JNZ L00    ; 14: 3,0
+"
+        );
+    }
+
+    #[test]
+    fn test_pseudo_code() {
+        let disassembly = disassemble(&FINAL_PROGRAM);
+        assert_eq!(
+            disassembly.pseudo_code(),
+            "L00:
+    B = A % 8
+    B = B ^ 1
+    C = A >> B
+    B = B ^ 5
+    A = A >> 3
+    B = B ^ C
+    out(B % 8)
+    if A != 0 goto L00"
+        );
+    }
+
+    #[rstest]
+    #[case::misaligned(&[0, 1, 3, 1], vec![Warning::MisalignedJump { address: 2, target: 1 }])]
+    #[case::reserved(&[5, 7, 1, 7], vec![Warning::ReservedOperand { address: 0 }])]
+    #[case::trailing(&[0, 1, 5], vec![Warning::TrailingByte { address: 2 }])]
+    #[case::jump_past_end(&[3, 4], vec![])]
+    fn test_warnings(#[case] bytecode: &[u8], #[case] expected: Vec<Warning>) {
+        assert_eq!(disassemble(bytecode).warnings, expected);
+    }
+
+    #[test]
+    fn test_listing_invalid() {
+        let disassembly = disassemble(&[5, 7, 3, 1, 4]);
+        assert_eq!(
+            disassembly.to_string(),
+            "    .byte 5,7  ; 00: 5,7
+    .byte 3,1  ; 02: 3,1
+    .byte 4    ; 04: 4
+"
+        );
+        assert_eq!(
+            disassembly.pseudo_code(),
+            "    invalid 5,7
+    if A != 0 goto 1 (misaligned)
+    invalid 4"
+        );
+    }
+}
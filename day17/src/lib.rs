@@ -1,4 +1,10 @@
+pub mod assembler;
+pub mod compiled;
+pub mod debugger;
+pub mod disassembler;
 pub mod puzzle;
 pub mod puzzle_parse;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
+pub mod quine;
+pub mod symbolic;
@@ -1,10 +1,16 @@
 use puzzle_part1::Part1;
 use puzzle_part2::Part2;
 
+pub mod assembler;
+pub mod compiled;
+pub mod debugger;
+pub mod disassembler;
 pub mod puzzle;
 pub mod puzzle_parse;
 pub mod puzzle_part1;
 pub mod puzzle_part2;
+pub mod quine;
+pub mod symbolic;
 
 fn main() {
     let puzzle_input = include_str!("../input.txt");
@@ -14,6 +20,13 @@ fn main() {
         Err(e) => {
             println!("Error: {e:?}");
         }
+        Ok(input) if std::env::args().nth(1).as_deref() == Some("debug") => {
+            let program = input.get_program();
+            let mut debugger = debugger::debugger(input.registers, &program, &input.bytecode);
+            debugger
+                .run_interactive(std::io::stdin().lock(), std::io::stdout())
+                .expect("debugger I/O failed");
+        }
         Ok(input) => {
             println!("day17 - part 1: {}", input.part1());
             println!("day17 - part 2: {}", input.part2());
@@ -1,4 +1,7 @@
 use std::fmt::Display;
+use std::marker::PhantomData;
+
+use aoc_vm::{Effect, InstructionSet, Machine, Word};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct PuzzleInput {
@@ -8,7 +11,7 @@ pub struct PuzzleInput {
 
 impl PuzzleInput {
     pub fn validate_assumptions(&self) -> Result<(), String> {
-        if self.bytecode.len() % 2 != 0 {
+        if !self.bytecode.len().is_multiple_of(2) {
             return Err("Invalid number of opcodes".to_string());
         }
 
@@ -105,75 +108,193 @@ impl Display for OpCode {
     }
 }
 
-pub struct VirtualMachine<'a> {
-    pub registers: [i64; 3],
-    pub program: &'a Vec<OpCode>,
-    pub pc: usize,
-}
+/// The 3-bit computer from the puzzle: registers A, B and C, and two-byte instructions. The
+/// word type defaults to the `i64` of the puzzle input, but wider words work too.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct ThreeBitComputer<W = i64>(PhantomData<W>);
 
-impl<'a> VirtualMachine<'a> {
-    pub fn new(registers: [i64; 3], program: &'a Vec<OpCode>) -> Self {
-        VirtualMachine {
-            registers,
-            program,
-            pc: 0,
-        }
+fn interpret_operand<W: Word>(operand: &Operand, registers: &[W]) -> W {
+    match operand {
+        Operand::Literal(value) => W::from_u8(*value),
+        Operand::RegisterA => registers[0],
+        Operand::RegisterB => registers[1],
+        Operand::RegisterC => registers[2],
     }
+}
 
-    fn interpret_operand(&self, operand: &Operand) -> i64 {
-        match operand {
-            Operand::Literal(value) => *value as i64,
-            Operand::RegisterA => self.registers[0],
-            Operand::RegisterB => self.registers[1],
-            Operand::RegisterC => self.registers[2],
-        }
-    }
+impl<W: Word> InstructionSet for ThreeBitComputer<W> {
+    type Word = W;
+    type Instruction = OpCode;
+    type Output = u8;
 
-    pub fn run_step(&mut self) -> Option<u8> {
-        let opcode = &self.program[self.pc];
+    fn execute(opcode: &OpCode, registers: &mut [W], _pc: usize) -> Effect<u8> {
+        let a = registers[0];
         match opcode {
             OpCode::Adv(operand) => {
-                self.registers[0] >>= self.interpret_operand(operand);
+                registers[0] = a.shift_right(interpret_operand(operand, registers));
             }
             OpCode::Bdv(operand) => {
-                self.registers[1] = self.registers[0] >> self.interpret_operand(operand);
+                registers[1] = a.shift_right(interpret_operand(operand, registers));
             }
             OpCode::Cdv(operand) => {
-                self.registers[2] = self.registers[0] >> self.interpret_operand(operand);
+                registers[2] = a.shift_right(interpret_operand(operand, registers));
             }
             OpCode::Bxl(value) => {
-                self.registers[1] ^= *value as i64;
+                registers[1] = registers[1] ^ W::from_u8(*value);
             }
             OpCode::Bst(operand) => {
-                self.registers[1] = self.interpret_operand(operand) % 8;
+                registers[1] = W::from_u8(interpret_operand(operand, registers).low_bits(3));
             }
             OpCode::Jnz(value) => {
-                if self.registers[0] != 0 {
-                    self.pc = *value as usize;
-                    return None; // prevent the pc from incrementing
+                if a != W::ZERO {
+                    // The target is a byte address, and each instruction is two bytes. At an odd
+                    // target, the real machine would read an operand as an opcode, which a
+                    // decoded program can't do, so it halts instead.
+                    if value % 2 == 1 {
+                        return Effect::Halt;
+                    }
+                    return Effect::Jump(*value as usize / 2);
                 }
             }
             OpCode::Bxc => {
-                self.registers[1] ^= self.registers[2];
+                registers[1] = registers[1] ^ registers[2];
             }
             OpCode::Out(operand) => {
-                self.pc += 1;
-                return Some((self.interpret_operand(operand) % 8) as u8);
+                return Effect::Output(interpret_operand(operand, registers).low_bits(3));
             }
         };
-        self.pc += 1;
-        None
+        Effect::Next
+    }
+
+    // Addresses are byte addresses, like in the bytecode
+    fn address(pc: usize) -> usize {
+        pc * 2
     }
 }
 
-impl Iterator for VirtualMachine<'_> {
-    type Item = u8;
-    fn next(&mut self) -> Option<u8> {
-        while self.pc < self.program.len() {
-            if let Some(output) = self.run_step() {
-                return Some(output);
+pub type VirtualMachine<'a, W = i64> = Machine<'a, ThreeBitComputer<W>, 3>;
+
+/// One executed instruction, as recorded by a tracing `VirtualMachine`.
+pub type TraceEntry<W = i64> = aoc_vm::TraceEntry<ThreeBitComputer<W>, 3>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_program() -> Vec<OpCode> {
+        PuzzleInput {
+            registers: [0, 0, 0],
+            bytecode: vec![0, 1, 5, 4, 3, 0],
+        }
+        .get_program()
+    }
+
+    #[test]
+    fn test_trace() {
+        let program = example_program();
+        let mut vm = VirtualMachine::new([4, 0, 0], &program).with_trace();
+        assert_eq!(vm.by_ref().collect::<Vec<_>>(), vec![2, 1, 0]);
+        assert_eq!(vm.cycles, 9);
+
+        let trace = vm.trace().unwrap();
+        assert_eq!(trace.len(), 9);
+        assert_eq!(
+            trace[1],
+            TraceEntry {
+                cycle: 1,
+                pc: 1,
+                instruction: OpCode::Out(Operand::RegisterA),
+                before: [2, 0, 0],
+                after: [2, 0, 0],
+                output: Some(2),
             }
+        );
+        assert_eq!(
+            trace[0].to_string(),
+            "#0    00: ADV 1  A=4 B=0 C=0 -> A=2 B=0 C=0"
+        );
+        assert_eq!(
+            trace[1].to_string(),
+            "#1    02: OUT A  A=2 B=0 C=0 -> A=2 B=0 C=0 out 2"
+        );
+    }
+
+    #[test]
+    fn test_profile() {
+        let program = example_program();
+        let mut vm = VirtualMachine::new([4, 0, 0], &program).with_profile();
+        vm.by_ref().for_each(drop);
+        assert_eq!(vm.profile(), Some(&[3, 3, 3][..]));
+        assert_eq!(vm.trace(), None);
+    }
+
+    #[test]
+    fn test_instruction_limit() {
+        // BXL 1 / JNZ 0 never terminates while A is non-zero
+        let program = vec![OpCode::Bxl(1), OpCode::Jnz(0)];
+        let mut vm = VirtualMachine::new([1, 0, 0], &program).with_instruction_limit(100);
+        assert_eq!(vm.next(), None);
+        assert!(vm.limit_reached());
+        assert_eq!(vm.cycles, 100);
+
+        let program = example_program();
+        let mut vm = VirtualMachine::new([4, 0, 0], &program).with_instruction_limit(100);
+        vm.by_ref().for_each(drop);
+        assert!(!vm.limit_reached());
+    }
+
+    #[test]
+    fn test_odd_jump_halts() {
+        // OUT A / JNZ 3: the jump lands on the operand of the JNZ itself
+        let program = vec![OpCode::Out(Operand::RegisterA), OpCode::Jnz(3)];
+        let mut vm = VirtualMachine::new([1, 0, 0], &program).with_instruction_limit(100);
+        assert_eq!(vm.by_ref().collect::<Vec<_>>(), vec![1]);
+        assert!(vm.is_halted());
+        assert_eq!(vm.cycles, 2);
+    }
+
+    #[test]
+    fn test_large_shift() {
+        // ADV B / CDV B / OUT A: shifting by 64 or more used to overflow
+        let program = vec![
+            OpCode::Adv(Operand::RegisterB),
+            OpCode::Cdv(Operand::RegisterB),
+            OpCode::Out(Operand::RegisterA),
+        ];
+        let mut vm = VirtualMachine::new([-5, 70, 0], &program);
+        assert_eq!(vm.by_ref().collect::<Vec<_>>(), vec![7]);
+        assert_eq!(vm.registers, [-1, 70, -1]);
+
+        let mut vm = VirtualMachine::<u128>::new([u128::MAX, 127, 0], &program);
+        assert_eq!(vm.by_ref().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(vm.registers, [1, 127, 0]);
+    }
+
+    #[test]
+    fn test_wide_registers() {
+        use bnum::types::U256;
+
+        let program = PuzzleInput {
+            registers: [0, 0, 0],
+            bytecode: vec![2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0],
         }
-        None
+        .get_program();
+        let a: i64 = 164516454365621;
+        let narrow = VirtualMachine::new([a, 0, 0], &program).collect::<Vec<_>>();
+        let wide = VirtualMachine::<u128>::new([a as u128, 0, 0], &program).collect::<Vec<_>>();
+        assert_eq!(narrow, wide);
+
+        // An A that doesn't fit in an i64 outputs 3 bits for every 3 bits of A
+        let wide = VirtualMachine::<u128>::new([a as u128 | 1 << 120, 0, 0], &program);
+        assert_eq!(wide.count(), 41);
+
+        // Big integers work as registers too, for A past 128 bits
+        let big_a = U256::from(a as u128 | 1 << 120);
+        let big = VirtualMachine::<U256>::new([big_a, U256::ZERO, U256::ZERO], &program);
+        let wide = VirtualMachine::<u128>::new([a as u128 | 1 << 120, 0, 0], &program);
+        assert_eq!(big.collect::<Vec<_>>(), wide.collect::<Vec<_>>());
+        let big_a = U256::from(a as u128) | U256::ONE << 200;
+        let big = VirtualMachine::<U256>::new([big_a, U256::ZERO, U256::ZERO], &program);
+        assert_eq!(big.count(), 67);
     }
 }
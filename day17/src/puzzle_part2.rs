@@ -1,53 +1,18 @@
-use crate::puzzle::{OpCode, PuzzleInput, VirtualMachine};
+use crate::puzzle::PuzzleInput;
+use crate::quine::find_quine;
 
 pub trait Part2 {
     fn part2(&self) -> String;
 }
 
-fn brute_force(
-    program: &Vec<OpCode>,
-    target: &Vec<u8>,
-    a_register_at_start: i64,
-    offset: usize,
-) -> Result<i64, ()> {
-    // Try each possible permuation for 8 bits
-    for permutation in 0..8 {
-        // Inject the bits under test into the register
-        let value_under_test = a_register_at_start | (permutation << (offset * 3));
-        let vm = VirtualMachine::new([value_under_test, 0, 0], program);
-
-        // Run the program
-        let mut output = Vec::<_>::with_capacity(target.len());
-        output.extend(vm);
-
-        // Pad the output with 0, as repeated zeroes might not be outputted
-        output.resize(target.len(), 0);
-
-        if &output == target {
-            // We've found the answer
-            return Ok(value_under_test);
-        }
-
-        if output[offset] == target[offset] {
-            // If the current offset matches, then try next offset, too.
-            if let Ok(result) = brute_force(program, target, value_under_test, offset - 1) {
-                return Ok(result);
-            }
-        }
-    }
-    Err(())
-}
-
 impl Part2 for PuzzleInput {
     fn part2(&self) -> String {
-        // Based on reverse engineering the program, i've determined
-        // that the program is processing 3 bits at a time. This means that
-        // there are only 8 ways to set the bits. This means that we can brute force each set of 3 bits.
-
-        let program = self.get_program();
-        brute_force(&program, &self.bytecode, 0, self.bytecode.len() - 1)
-            .unwrap()
-            .to_string()
+        // The program consumes A a few bits at a time, so we can search for A one digit at a
+        // time, starting with the last output. See `quine` for the details.
+        match find_quine(self) {
+            Ok(a) => a.to_string(),
+            Err(error) => format!("UNSOLVABLE: {error}"),
+        }
     }
 }
 
@@ -58,6 +23,10 @@ mod tests {
     use super::*;
 
     #[rstest]
+    #[case::part2_example(
+        "Register A: 2024\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5,4,3,0",
+        "117440"
+    )]
     #[case::final_input( include_str!("../input.txt"), "164516454365621")]
     fn test_solve(#[case] input: &str, #[case] expected: &str) {
         let input = PuzzleInput::try_from(input).unwrap();
@@ -0,0 +1,227 @@
+use std::fmt::Display;
+
+use crate::compiled::{compile, CompiledProgram};
+use crate::puzzle::{OpCode, Operand, PuzzleInput};
+
+// Each candidate run only needs a few instructions per output, this is a generous upper bound.
+const INSTRUCTION_LIMIT: usize = 100_000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum QuineError {
+    /// The program isn't a loop that consumes a fixed number of bits of A per output, so the
+    /// backward digit search doesn't apply.
+    Unsupported(String),
+    /// There's nothing to search for.
+    EmptyTarget,
+    /// The search was exhaustive, no value of A produces the target.
+    NoSolution,
+}
+
+impl Display for QuineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuineError::Unsupported(reason) => write!(f, "unsupported program: {reason}"),
+            QuineError::EmptyTarget => write!(f, "the target output is empty"),
+            QuineError::NoSolution => write!(f, "no value of A produces the target output"),
+        }
+    }
+}
+
+fn reads(operand: &Operand, register: &Operand) -> bool {
+    operand == register
+}
+
+/// Checks that the program is a single loop that shifts a fixed number of bits out of A, outputs
+/// one value per iteration, and doesn't carry B or C over between iterations. Returns the number
+/// of bits consumed per iteration.
+pub fn bits_per_output(program: &[OpCode]) -> Result<u8, QuineError> {
+    let unsupported = |reason: &str| Err(QuineError::Unsupported(reason.to_string()));
+
+    let Some((OpCode::Jnz(0), body)) = program.split_last() else {
+        return unsupported("the program must end with JNZ 0");
+    };
+    if body.iter().any(|opcode| matches!(opcode, OpCode::Jnz(_))) {
+        return unsupported("the loop body must not contain jumps");
+    }
+    if body
+        .iter()
+        .filter(|opcode| matches!(opcode, OpCode::Out(_)))
+        .count()
+        != 1
+    {
+        return unsupported("the loop must output exactly once");
+    }
+
+    let shifts = body
+        .iter()
+        .filter_map(|opcode| match opcode {
+            OpCode::Adv(operand) => Some(operand),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let bits = match shifts[..] {
+        [Operand::Literal(bits @ 1..=3)] => *bits,
+        [_] => return unsupported("A must be shifted by a constant, non-zero number of bits"),
+        _ => return unsupported("A must be shifted exactly once per loop"),
+    };
+
+    // B and C must be written before they're read, otherwise the output depends on the
+    // previous iteration.
+    let mut written = [false; 2];
+    for opcode in body {
+        let (read_b, read_c, writes) = match opcode {
+            OpCode::Adv(operand) | OpCode::Out(operand) => (
+                reads(operand, &Operand::RegisterB),
+                reads(operand, &Operand::RegisterC),
+                None,
+            ),
+            OpCode::Bst(operand) | OpCode::Bdv(operand) | OpCode::Cdv(operand) => (
+                reads(operand, &Operand::RegisterB),
+                reads(operand, &Operand::RegisterC),
+                Some(if matches!(opcode, OpCode::Cdv(_)) {
+                    1
+                } else {
+                    0
+                }),
+            ),
+            OpCode::Bxl(_) => (true, false, Some(0)),
+            OpCode::Bxc => (true, true, Some(0)),
+            OpCode::Jnz(_) => unreachable!("checked above"),
+        };
+        if (read_b && !written[0]) || (read_c && !written[1]) {
+            return unsupported("B and C must be set from A before they're used");
+        }
+        if let Some(register) = writes {
+            written[register] = true;
+        }
+    }
+
+    Ok(bits)
+}
+
+// Finds the smallest A that produces target[index..], given the bits of A that are already known
+fn search(
+    program: &CompiledProgram,
+    registers: [i64; 3],
+    target: &[u8],
+    bits: u8,
+    known: i64,
+    index: usize,
+) -> Option<i64> {
+    (0..1 << bits).find_map(|digit| {
+        let a = known.checked_mul(1 << bits)? | digit;
+        let run = program
+            .run([a, registers[1], registers[2]])
+            .with_instruction_limit(INSTRUCTION_LIMIT);
+
+        // Verify the whole output, not just the new digit
+        if !run.eq(target[index..].iter().copied()) {
+            return None;
+        }
+        match index {
+            0 => Some(a),
+            _ => search(program, registers, target, bits, a, index - 1),
+        }
+    })
+}
+
+/// The smallest initial value of A that makes the program output `target`.
+///
+/// The program consumes A a few bits at a time, and the last output only depends on the most
+/// significant bits. So the bits of A are found from the most significant end, working backwards
+/// from the last output.
+pub fn find_register_a(input: &PuzzleInput, target: &[u8]) -> Result<i64, QuineError> {
+    let program = input.get_program();
+    let bits = bits_per_output(&program)?;
+    if target.is_empty() {
+        return Err(QuineError::EmptyTarget);
+    }
+
+    // Every candidate is a complete run of the program, so compiling it first pays off
+    let compiled = compile(&program);
+    search(
+        &compiled,
+        input.registers,
+        target,
+        bits,
+        0,
+        target.len() - 1,
+    )
+    .ok_or(QuineError::NoSolution)
+}
+
+/// The smallest initial value of A that makes the program output its own bytecode.
+pub fn find_quine(input: &PuzzleInput) -> Result<i64, QuineError> {
+    find_register_a(input, &input.bytecode)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::puzzle::VirtualMachine;
+
+    fn puzzle(bytecode: &[u8]) -> PuzzleInput {
+        PuzzleInput {
+            registers: [0, 0, 0],
+            bytecode: bytecode.to_vec(),
+        }
+    }
+
+    #[rstest]
+    #[case::example(&[0, 3, 5, 4, 3, 0], Ok(117440))]
+    #[case::final_program(&[2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0], Ok(164516454365621))]
+    #[case::part1_example(&[0, 1, 5, 4, 3, 0], Err(QuineError::NoSolution))]
+    fn test_find_quine(#[case] bytecode: &[u8], #[case] expected: Result<i64, QuineError>) {
+        let input = puzzle(bytecode);
+        let result = find_quine(&input);
+        assert_eq!(result, expected);
+
+        if let Ok(a) = result {
+            let program = input.get_program();
+            let output = VirtualMachine::new([a, 0, 0], &program).collect::<Vec<_>>();
+            assert_eq!(output, bytecode);
+        }
+    }
+
+    #[rstest]
+    #[case::zero(0)]
+    #[case::one_bit_per_output(0b101101)]
+    #[case::large(123456789)]
+    /// Finds an A for the output of a known A. That must be the same A or a smaller one.
+    fn test_find_register_a_target(#[case] known_a: i64) {
+        // ADV 1 / OUT A / JNZ 0
+        let input = puzzle(&[0, 1, 5, 4, 3, 0]);
+        let program = input.get_program();
+        let run = |a| VirtualMachine::new([a, 0, 0], &program).collect::<Vec<_>>();
+
+        let target = run(known_a);
+        let a = find_register_a(&input, &target).unwrap();
+        assert!(a <= known_a);
+        assert_eq!(run(a), target);
+    }
+
+    #[rstest]
+    #[case::no_loop(&[0, 3, 5, 4], "the program must end with JNZ 0")]
+    #[case::two_jumps(&[3, 0, 0, 3, 5, 4, 3, 0], "the loop body must not contain jumps")]
+    #[case::no_output(&[0, 3, 3, 0], "the loop must output exactly once")]
+    #[case::variable_shift(&[2, 4, 0, 5, 5, 4, 3, 0], "A must be shifted by a constant, non-zero number of bits")]
+    #[case::no_shift(&[5, 4, 3, 0], "A must be shifted exactly once per loop")]
+    #[case::carried_b(&[1, 3, 0, 3, 5, 5, 3, 0], "B and C must be set from A before they're used")]
+    #[case::carried_c(&[0, 3, 5, 6, 3, 0], "B and C must be set from A before they're used")]
+    fn test_unsupported(#[case] bytecode: &[u8], #[case] reason: &str) {
+        assert_eq!(
+            find_quine(&puzzle(bytecode)),
+            Err(QuineError::Unsupported(reason.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_empty_target() {
+        assert_eq!(
+            find_register_a(&puzzle(&[0, 3, 5, 4, 3, 0]), &[]),
+            Err(QuineError::EmptyTarget)
+        );
+    }
+}
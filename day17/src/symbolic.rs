@@ -0,0 +1,428 @@
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::puzzle::{OpCode, Operand, PuzzleInput};
+
+// Bit masks are u64, one bit per bit of A
+const WORD_BITS: usize = 64;
+
+// Constraint propagation enumerates all values of the newly introduced bits of each output
+const MAX_FREE_BITS: u32 = 20;
+
+/// A register value, as an expression of the initial value of register A.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expr {
+    Const(i64),
+    A,
+    Shr(Rc<Expr>, Rc<Expr>),
+    Xor(Rc<Expr>, Rc<Expr>),
+    Mod8(Rc<Expr>),
+}
+
+impl Expr {
+    /// `value >> amount`, simplified where possible.
+    pub fn new_shr(value: Expr, amount: Expr) -> Expr {
+        match (value, amount) {
+            (value, Expr::Const(0)) => value,
+            (Expr::Const(value), Expr::Const(amount)) => Expr::Const(shift_right(value, amount)),
+            (Expr::Shr(inner, first), Expr::Const(second)) if matches!(*first, Expr::Const(_)) => {
+                let Expr::Const(first) = *first else {
+                    unreachable!()
+                };
+                Expr::Shr(inner, Rc::new(Expr::Const(first + second)))
+            }
+            (value, amount) => Expr::Shr(Rc::new(value), Rc::new(amount)),
+        }
+    }
+
+    /// `left ^ right`, simplified where possible.
+    pub fn new_xor(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (Expr::Const(left), Expr::Const(right)) => Expr::Const(left ^ right),
+            (value, Expr::Const(0)) | (Expr::Const(0), value) => value,
+            (Expr::Xor(inner, constant), Expr::Const(right))
+                if matches!(*constant, Expr::Const(_)) =>
+            {
+                let Expr::Const(left) = *constant else {
+                    unreachable!()
+                };
+                Expr::new_xor((*inner).clone(), Expr::Const(left ^ right))
+            }
+            (left, right) => Expr::Xor(Rc::new(left), Rc::new(right)),
+        }
+    }
+
+    /// `value % 8`, simplified where possible.
+    pub fn new_mod8(value: Expr) -> Expr {
+        match value {
+            Expr::Const(value) => Expr::Const(value.rem_euclid(8)),
+            Expr::Mod8(_) => value,
+            value => Expr::Mod8(Rc::new(value)),
+        }
+    }
+
+    /// Evaluates the expression for a concrete initial value of A.
+    pub fn eval(&self, a: i64) -> i64 {
+        match self {
+            Expr::Const(value) => *value,
+            Expr::A => a,
+            Expr::Shr(value, amount) => shift_right(value.eval(a), amount.eval(a)),
+            Expr::Xor(left, right) => left.eval(a) ^ right.eval(a),
+            Expr::Mod8(value) => value.eval(a).rem_euclid(8),
+        }
+    }
+
+    // An upper bound of the value, if there is one
+    fn max_value(&self) -> Option<u64> {
+        match self {
+            Expr::Const(value) => u64::try_from(*value).ok(),
+            Expr::A => None,
+            Expr::Shr(value, _) => value.max_value(),
+            Expr::Xor(left, right) => {
+                let max = left.max_value()?.max(right.max_value()?);
+                Some(max.checked_next_power_of_two().map_or(u64::MAX, |p| {
+                    if p == max {
+                        2 * p - 1
+                    } else {
+                        p - 1
+                    }
+                }))
+            }
+            Expr::Mod8(_) => Some(7),
+        }
+    }
+
+    /// For every bit of the value, the bits of A it depends on.
+    pub fn bit_dependencies(&self) -> [u64; WORD_BITS] {
+        match self {
+            Expr::Const(_) => [0; WORD_BITS],
+            Expr::A => std::array::from_fn(|bit| 1 << bit),
+            Expr::Xor(left, right) => {
+                let (left, right) = (left.bit_dependencies(), right.bit_dependencies());
+                std::array::from_fn(|bit| left[bit] | right[bit])
+            }
+            Expr::Mod8(value) => {
+                let value = value.bit_dependencies();
+                std::array::from_fn(|bit| if bit < 3 { value[bit] } else { 0 })
+            }
+            Expr::Shr(value, amount) => {
+                let value = value.bit_dependencies();
+                let shift_dependencies = amount.dependencies();
+                let max_amount = amount
+                    .max_value()
+                    .map_or(WORD_BITS, |max| (max as usize).min(WORD_BITS));
+                let min_amount = match **amount {
+                    Expr::Const(amount) => amount as usize,
+                    _ => 0,
+                };
+                // Any bit in range of the shift can end up here, and the shift amount itself
+                // affects every bit
+                std::array::from_fn(|bit| {
+                    (min_amount..=max_amount)
+                        .filter_map(|shift| value.get(bit + shift))
+                        .fold(shift_dependencies, |acc, d| acc | d)
+                })
+            }
+        }
+    }
+
+    /// The bits of A the value depends on.
+    pub fn dependencies(&self) -> u64 {
+        self.bit_dependencies().iter().fold(0, |acc, d| acc | d)
+    }
+}
+
+// Shifting everything out gives 0 (or -1 for negative numbers), rather than overflowing
+fn shift_right(value: i64, amount: i64) -> i64 {
+    match u32::try_from(amount) {
+        Ok(amount) if amount < 64 => value >> amount,
+        _ if amount < 0 => panic!("negative shift amount"),
+        _ => value >> 63,
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Const(value) => write!(f, "{value}"),
+            Expr::A => write!(f, "A"),
+            Expr::Shr(value, amount) => write!(f, "({value} >> {amount})"),
+            Expr::Xor(left, right) => write!(f, "({left} ^ {right})"),
+            Expr::Mod8(value) => write!(f, "({value} % 8)"),
+        }
+    }
+}
+
+/// The result of symbolically running a program for a fixed number of loop iterations.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Unrolled {
+    /// The expression for every output, in order.
+    pub outputs: Vec<Expr>,
+    /// The value of A at every jump. It must be non-zero for every jump that is taken, and zero for
+    /// the last one.
+    pub jump_conditions: Vec<Expr>,
+}
+
+/// Runs the program on symbolic registers, taking every jump `iterations - 1` times.
+pub fn unroll(input: &PuzzleInput, iterations: usize) -> Unrolled {
+    let program = input.get_program();
+    let mut registers = [
+        Expr::A,
+        Expr::Const(input.registers[1]),
+        Expr::Const(input.registers[2]),
+    ];
+    let mut outputs = Vec::new();
+    let mut jump_conditions = Vec::new();
+    let mut pc = 0;
+
+    let operand = |registers: &[Expr; 3], operand: &Operand| match operand {
+        Operand::Literal(value) => Expr::Const(*value as i64),
+        Operand::RegisterA => registers[0].clone(),
+        Operand::RegisterB => registers[1].clone(),
+        Operand::RegisterC => registers[2].clone(),
+    };
+
+    while pc < program.len() {
+        match &program[pc] {
+            OpCode::Adv(o) => {
+                registers[0] = Expr::new_shr(registers[0].clone(), operand(&registers, o))
+            }
+            OpCode::Bdv(o) => {
+                registers[1] = Expr::new_shr(registers[0].clone(), operand(&registers, o))
+            }
+            OpCode::Cdv(o) => {
+                registers[2] = Expr::new_shr(registers[0].clone(), operand(&registers, o))
+            }
+            OpCode::Bxl(value) => {
+                registers[1] = Expr::new_xor(registers[1].clone(), Expr::Const(*value as i64))
+            }
+            OpCode::Bst(o) => registers[1] = Expr::new_mod8(operand(&registers, o)),
+            OpCode::Bxc => registers[1] = Expr::new_xor(registers[1].clone(), registers[2].clone()),
+            OpCode::Out(o) => outputs.push(Expr::new_mod8(operand(&registers, o))),
+            OpCode::Jnz(target) => {
+                jump_conditions.push(registers[0].clone());
+                if jump_conditions.len() < iterations {
+                    pc = *target as usize / 2;
+                    continue;
+                }
+            }
+        }
+        pc += 1;
+    }
+
+    Unrolled {
+        outputs,
+        jump_conditions,
+    }
+}
+
+impl Unrolled {
+    /// The bits of A that affect output `k`, or None if the unrolled path has no output `k`.
+    pub fn affecting_bits(&self, k: usize) -> Option<Vec<usize>> {
+        let mask = self.outputs.get(k)?.dependencies();
+        Some(
+            (0..WORD_BITS)
+                .filter(|bit| mask & (1 << bit) != 0)
+                .collect(),
+        )
+    }
+
+    /// True if running the program with this value of A follows the unrolled path and produces
+    /// the target.
+    pub fn verify(&self, a: i64, target: &[u8]) -> bool {
+        let (last, taken) = match self.jump_conditions.split_last() {
+            Some((last, taken)) => (Some(last), taken),
+            None => (None, &[][..]),
+        };
+        self.outputs.len() == target.len()
+            && taken.iter().all(|condition| condition.eval(a) != 0)
+            && last.is_none_or(|condition| condition.eval(a) == 0)
+            && self
+                .outputs
+                .iter()
+                .zip(target)
+                .all(|(output, &t)| output.eval(a) == t as i64)
+    }
+}
+
+// Spreads the low bits of `value` over the set bits of `mask`
+fn deposit_bits(value: u64, mask: u64) -> u64 {
+    let mut result = 0;
+    let mut remaining = mask;
+    let mut value = value;
+    while remaining != 0 {
+        let bit = remaining & remaining.wrapping_neg();
+        if value & 1 != 0 {
+            result |= bit;
+        }
+        value >>= 1;
+        remaining &= remaining - 1;
+    }
+    result
+}
+
+fn propagate(
+    unrolled: &Unrolled,
+    target: &[u8],
+    remaining: usize,
+    known: u64,
+    a: u64,
+    solutions: &mut Vec<i64>,
+) -> Result<(), String> {
+    let Some(k) = remaining.checked_sub(1) else {
+        solutions.push(a as i64);
+        return Ok(());
+    };
+
+    let free = unrolled.outputs[k].dependencies() & !known;
+    if free.count_ones() > MAX_FREE_BITS {
+        return Err(format!(
+            "output {k} depends on {} unknown bits",
+            free.count_ones()
+        ));
+    }
+
+    for assignment in 0..1u64 << free.count_ones() {
+        let candidate = a | deposit_bits(assignment, free);
+        // The sign bit would make A negative
+        if candidate > i64::MAX as u64 {
+            continue;
+        }
+        if unrolled.outputs[k].eval(candidate as i64) == target[k] as i64 {
+            propagate(unrolled, target, k, known | free, candidate, solutions)?;
+        }
+    }
+    Ok(())
+}
+
+/// Finds the smallest A that makes the program output `target`, by working back from the last
+/// output, and only trying the bits of A each output depends on. Bits that no output depends on are
+/// left at zero.
+pub fn solve(input: &PuzzleInput, target: &[u8]) -> Result<Option<i64>, String> {
+    let unrolled = unroll(input, target.len());
+    if unrolled.outputs.len() != target.len() {
+        return Err(format!(
+            "the program outputs {} values in {} iterations, instead of {}",
+            unrolled.outputs.len(),
+            target.len(),
+            target.len()
+        ));
+    }
+
+    let mut solutions = Vec::new();
+    propagate(&unrolled, target, target.len(), 0, 0, &mut solutions)?;
+
+    Ok(solutions
+        .into_iter()
+        .filter(|&a| unrolled.verify(a, target))
+        .min())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::quine::find_quine;
+
+    fn puzzle(bytecode: &[u8]) -> PuzzleInput {
+        PuzzleInput {
+            registers: [0, 0, 0],
+            bytecode: bytecode.to_vec(),
+        }
+    }
+
+    const FINAL_PROGRAM: [u8; 16] = [2, 4, 1, 1, 7, 5, 1, 5, 0, 3, 4, 4, 5, 5, 3, 0];
+
+    #[test]
+    fn test_simplify() {
+        let shifted = Expr::new_shr(Expr::new_shr(Expr::A, Expr::Const(3)), Expr::Const(3));
+        assert_eq!(shifted.to_string(), "(A >> 6)");
+        let xored = Expr::new_xor(Expr::new_xor(Expr::A, Expr::Const(1)), Expr::Const(5));
+        assert_eq!(xored.to_string(), "(A ^ 4)");
+        assert_eq!(
+            Expr::new_mod8(Expr::new_mod8(Expr::A)).to_string(),
+            "(A % 8)"
+        );
+        assert_eq!(Expr::new_mod8(Expr::Const(-1)), Expr::Const(7));
+    }
+
+    #[test]
+    fn test_unroll() {
+        let unrolled = unroll(&puzzle(&FINAL_PROGRAM), 2);
+        assert_eq!(
+            unrolled.outputs[0].to_string(),
+            "((((A % 8) ^ 4) ^ (A >> ((A % 8) ^ 1))) % 8)"
+        );
+        assert_eq!(
+            unrolled.outputs[1].to_string(),
+            "(((((A >> 3) % 8) ^ 4) ^ ((A >> 3) >> (((A >> 3) % 8) ^ 1))) % 8)"
+        );
+        assert_eq!(
+            unrolled
+                .jump_conditions
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>(),
+            vec!["(A >> 3)", "(A >> 6)"]
+        );
+    }
+
+    #[rstest]
+    #[case::example(&[0, 3, 5, 4, 3, 0], 0, vec![3, 4, 5])]
+    #[case::example_last(&[0, 3, 5, 4, 3, 0], 5, vec![18, 19, 20])]
+    #[case::final_program(&FINAL_PROGRAM, 0, (0..=9).collect())]
+    #[case::final_program_last(&FINAL_PROGRAM, 15, (45..=54).collect())]
+    fn test_affecting_bits(
+        #[case] bytecode: &[u8],
+        #[case] k: usize,
+        #[case] expected: Vec<usize>,
+    ) {
+        let unrolled = unroll(&puzzle(bytecode), 6.max(k + 1));
+        assert_eq!(unrolled.affecting_bits(k), Some(expected));
+    }
+
+    #[test]
+    fn test_affecting_bits_past_last_output() {
+        let unrolled = unroll(&puzzle(&[0, 3, 5, 4, 3, 0]), 6);
+        assert_eq!(unrolled.affecting_bits(6), None);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(12345)]
+    #[case(164516454365621)]
+    /// The symbolic outputs must match what the VM outputs.
+    fn test_eval_matches_vm(#[case] a: i64) {
+        let input = PuzzleInput {
+            registers: [a, 0, 0],
+            bytecode: FINAL_PROGRAM.to_vec(),
+        };
+        let program = input.get_program();
+        let output =
+            crate::puzzle::VirtualMachine::new(input.registers, &program).collect::<Vec<_>>();
+
+        let unrolled = unroll(&input, output.len());
+        assert!(unrolled.verify(a, &output));
+    }
+
+    #[rstest]
+    #[case::example(&[0, 3, 5, 4, 3, 0])]
+    #[case::final_program(&FINAL_PROGRAM)]
+    /// Constraint propagation must find the same answer as the quine search.
+    fn test_solve(#[case] bytecode: &[u8]) {
+        let input = puzzle(bytecode);
+        assert_eq!(
+            solve(&input, bytecode).unwrap(),
+            Some(find_quine(&input).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_solve_impossible() {
+        assert_eq!(
+            solve(&puzzle(&[0, 1, 5, 4, 3, 0]), &[0, 1, 5, 4, 3, 0]),
+            Ok(None)
+        );
+    }
+}
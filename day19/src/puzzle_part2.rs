@@ -1,25 +1,38 @@
+use aoc_memo::Memo;
+
 use crate::puzzle::PuzzleInput;
-use pathfinding::prelude::count_paths;
 
 pub trait Part2 {
     fn part2(&self) -> String;
 }
 
+// Counts the ways the pattern can be made from towels. The cache is keyed by the remaining
+// pattern, so it can be shared between targets that end in the same way.
+fn count_arrangements<'a>(
+    memo: &mut Memo<&'a str, usize>,
+    towel_patterns: &[&str],
+    pattern: &'a str,
+) -> usize {
+    if pattern.is_empty() {
+        return 1;
+    }
+
+    memo.get_or_compute(pattern, |memo| {
+        towel_patterns
+            .iter()
+            .filter_map(|&towel_pattern| pattern.strip_prefix(towel_pattern))
+            .map(|remaining| count_arrangements(memo, towel_patterns, remaining))
+            .sum()
+    })
+}
+
 impl Part2 for PuzzleInput<'_> {
     fn part2(&self) -> String {
+        let mut memo = Memo::with_capacity(self.target_patterns.len() * 64);
         self.target_patterns
             .iter()
             .map(|&target_pattern| {
-                count_paths(
-                    target_pattern,
-                    |pattern| {
-                        self.towel_patterns
-                            .iter()
-                            .filter_map(|&towel_pattern| pattern.strip_prefix(towel_pattern))
-                            .collect::<Vec<_>>()
-                    },
-                    |pattern| pattern.is_empty(),
-                )
+                count_arrangements(&mut memo, &self.towel_patterns, target_pattern)
             })
             .sum::<usize>()
             .to_string()